@@ -202,6 +202,7 @@ fn generate_asset_enum(
         .collect();
 
     entries.sort_by(|a, b| a.path().file_name().cmp(&b.path().file_name()));
+    let rigs = rig_names(assets_dir);
 
     // Write enum with strum derives including EnumProperty
     // Add Component derive for types used directly as components
@@ -213,6 +214,12 @@ fn generate_asset_enum(
         "#[derive({}Default, Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumCount, Display, EnumProperty, Reflect, Serialize, Deserialize)]",
         component_derive
     )?;
+    let component_reflect = if is_component { "Component, " } else { "" };
+    writeln!(
+        f,
+        "#[reflect({}Default, Serialize, Deserialize)]",
+        component_reflect
+    )?;
     writeln!(f, "pub enum {} {{", enum_name)?;
 
     let mut first = true;
@@ -269,6 +276,32 @@ fn generate_asset_enum(
             props.push(format!("{} = \"{}\"", clean_name, path));
         }
 
+        // MakeHuman uuid and tags from the mhclo/proxy, used to resolve .mhm references
+        let meta_path = ["mhclo", "proxy"]
+            .iter()
+            .map(|ext| asset_dir.join(format!("{}.{}", dir_name_str, ext)))
+            .find(|path| path.exists());
+        if let Some(meta_path) = meta_path {
+            if let Some(uuid) = read_uuid(&meta_path) {
+                props.push(format!("uuid = \"{}\"", uuid));
+            }
+            let tags = read_tags(&meta_path);
+            if !tags.is_empty() {
+                props.push(format!("tags = \"{}\"", tags.join(",")));
+            }
+        }
+
+        // Own vertex bone weights, `<name>.<rig>.mhw` for one rig, any other .mhw for the default rig
+        if pattern.required.contains(&"mhclo") {
+            for (rig, path) in part_weights(&asset_dir, &rigs) {
+                let path = format!("make_human/{}/{}/{}", subdir, dir_name_str, path);
+                match rig {
+                    Some(rig) => props.push(format!("mhw_{} = \"{}\"", rig, path)),
+                    None => props.push(format!("mhw = \"{}\"", path)),
+                }
+            }
+        }
+
         // Textures - scan for common texture files
         for texture_type in pattern.textures {
             let file_path = asset_dir.join(format!("{}_{}.png", dir_name_str, texture_type));
@@ -426,6 +459,7 @@ fn generate_eyes_enum(f: &mut File, assets_dir: &Path) -> io::Result<()> {
         f,
         "#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumCount, Display, EnumProperty, Reflect, Serialize, Deserialize)]"
     )?;
+    writeln!(f, "#[reflect(Component, Default, Serialize, Deserialize)]")?;
     writeln!(f, "pub enum Eyes {{")?;
 
     let mut first = true;
@@ -436,6 +470,9 @@ fn generate_eyes_enum(f: &mut File, assets_dir: &Path) -> io::Result<()> {
             let obj_path = format!("make_human/eyes/{}/{}.obj", mesh_dir, mesh_dir);
             let mhmat_path = format!("make_human/eyes/materials/{}.mhmat", mat_stem);
             let thumb_path = format!("make_human/eyes/{}/{}.thumb", mesh_dir, mesh_dir);
+            let uuid = read_uuid(&eyes_dir.join(mesh_dir).join(format!("{}.mhclo", mesh_dir)))
+                .map(|uuid| format!(", uuid = \"{}\"", uuid))
+                .unwrap_or_default();
 
             writeln!(f, "    /// {} mesh with {} material", mesh_dir, mat_stem)?;
             if first {
@@ -444,8 +481,8 @@ fn generate_eyes_enum(f: &mut File, assets_dir: &Path) -> io::Result<()> {
             }
             writeln!(
                 f,
-                "    #[strum(props(mhclo = \"{}\", obj = \"{}\", mhmat = \"{}\", thumb = \"{}\"))]",
-                mhclo_path, obj_path, mhmat_path, thumb_path
+                "    #[strum(props(mhclo = \"{}\", obj = \"{}\", mhmat = \"{}\", thumb = \"{}\"{}))]",
+                mhclo_path, obj_path, mhmat_path, thumb_path, uuid
             )?;
             writeln!(f, "    {},", variant_name)?;
         }
@@ -490,6 +527,7 @@ fn generate_pose_enum(f: &mut File, assets_dir: &Path) -> io::Result<()> {
         f,
         "#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumCount, Display, EnumProperty, Reflect, Serialize, Deserialize)]"
     )?;
+    writeln!(f, "#[reflect(Default, Serialize, Deserialize)]")?;
     writeln!(f, "pub enum PoseAsset {{")?;
 
     let mut first = true;
@@ -552,6 +590,7 @@ fn generate_rig_enum(f: &mut File, assets_dir: &Path) -> io::Result<()> {
         f,
         "#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumCount, Display, EnumProperty, Reflect, Serialize, Deserialize)]"
     )?;
+    writeln!(f, "#[reflect(Component, Default, Serialize, Deserialize)]")?;
     writeln!(f, "pub enum Rig {{")?;
 
     let mut first = true;
@@ -614,6 +653,66 @@ fn generate_rig_enum(f: &mut File, assets_dir: &Path) -> io::Result<()> {
     Ok(())
 }
 
+/// `uuid` line of a mhclo/proxy file, None if missing or not checked out (git lfs)
+fn read_uuid(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    content.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix("uuid ")
+            .map(|uuid| uuid.trim().to_string())
+    })
+}
+
+/// Rig directory names
+fn rig_names(assets_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(assets_dir.join("rigs")) else {
+        return vec![];
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect()
+}
+
+/// `.mhw` files in a part directory, with the `Rig` variant they're made for, None for the default rig
+fn part_weights(asset_dir: &Path, rigs: &[String]) -> Vec<(Option<String>, String)> {
+    let Ok(entries) = fs::read_dir(asset_dir) else {
+        return vec![];
+    };
+    let mut files: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".mhw"))
+        .collect();
+    files.sort();
+    files
+        .into_iter()
+        .map(|file| {
+            let stem = file.trim_end_matches(".mhw");
+            let rig = stem
+                .rsplit_once('.')
+                .map(|(_, rig)| rig)
+                .filter(|rig| rigs.iter().any(|r| r == rig))
+                .map(sanitize_name);
+            (rig, file)
+        })
+        .collect()
+}
+
+/// Lowercase `tag` lines of a mhclo/proxy file
+fn read_tags(path: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return vec![];
+    };
+    content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("tag "))
+        .map(|tag| tag.trim().to_lowercase().replace(['"', ',', '\\'], ""))
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
 fn sanitize_name(name: &str) -> String {
     name.split(|c: char| !c.is_alphanumeric())
         .filter(|part| !part.is_empty())
@@ -689,21 +788,14 @@ fn generate_morph_enums(f: &mut File, assets_dir: &Path) -> io::Result<()> {
 
     let categories = json.as_object().expect("target.json must be object");
 
-    let mut category_names: Vec<String> = categories
-        .keys()
-        .filter(|k| *k != "measure") // skip measure category
-        .cloned()
-        .collect();
+    let mut category_names: Vec<String> = categories.keys().cloned().collect();
     category_names.sort();
 
     // Collect morph entries per category
     let mut entries_by_category: HashMap<String, Vec<MorphEntry>> = HashMap::new();
 
+    let targets_dir = assets_dir.join("targets");
     for (category, data) in categories {
-        if category == "measure" {
-            continue;
-        }
-
         let mut entries: Vec<MorphEntry> = Vec::new();
         let mut seen_bases: std::collections::HashSet<String> = std::collections::HashSet::new();
 
@@ -724,9 +816,10 @@ fn generate_morph_enums(f: &mut File, assets_dir: &Path) -> io::Result<()> {
                                         name: target_name.to_string(),
                                         pair_type: PairType::Single,
                                         neg_path: None,
-                                        pos_path: Some(format!(
-                                            "make_human/targets/{}/{}.target",
-                                            category, target_name
+                                        pos_path: Some(target_file(
+                                            &targets_dir,
+                                            category,
+                                            target_name,
                                         )),
                                     });
                                 }
@@ -769,12 +862,8 @@ fn generate_morph_enums(f: &mut File, assets_dir: &Path) -> io::Result<()> {
                             entries.push(MorphEntry {
                                 name: sided_name,
                                 pair_type,
-                                neg_path: neg_left.map(|t| {
-                                    format!("make_human/targets/{}/{}.target", category, t)
-                                }),
-                                pos_path: pos_left.map(|t| {
-                                    format!("make_human/targets/{}/{}.target", category, t)
-                                }),
+                                neg_path: neg_left.map(|t| target_file(&targets_dir, category, t)),
+                                pos_path: pos_left.map(|t| target_file(&targets_dir, category, t)),
                             });
                         }
                     }
@@ -788,12 +877,8 @@ fn generate_morph_enums(f: &mut File, assets_dir: &Path) -> io::Result<()> {
                             entries.push(MorphEntry {
                                 name: sided_name,
                                 pair_type,
-                                neg_path: neg_right.map(|t| {
-                                    format!("make_human/targets/{}/{}.target", category, t)
-                                }),
-                                pos_path: pos_right.map(|t| {
-                                    format!("make_human/targets/{}/{}.target", category, t)
-                                }),
+                                neg_path: neg_right.map(|t| target_file(&targets_dir, category, t)),
+                                pos_path: pos_right.map(|t| target_file(&targets_dir, category, t)),
                             });
                         }
                     }
@@ -807,12 +892,10 @@ fn generate_morph_enums(f: &mut File, assets_dir: &Path) -> io::Result<()> {
                             entries.push(MorphEntry {
                                 name: base,
                                 pair_type,
-                                neg_path: neg_unsided.map(|t| {
-                                    format!("make_human/targets/{}/{}.target", category, t)
-                                }),
-                                pos_path: pos_unsided.map(|t| {
-                                    format!("make_human/targets/{}/{}.target", category, t)
-                                }),
+                                neg_path: neg_unsided
+                                    .map(|t| target_file(&targets_dir, category, t)),
+                                pos_path: pos_unsided
+                                    .map(|t| target_file(&targets_dir, category, t)),
                             });
                         }
                     }
@@ -841,6 +924,7 @@ fn generate_morph_enums(f: &mut File, assets_dir: &Path) -> io::Result<()> {
             f,
             "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumCount, Display, EnumProperty, Reflect, Serialize, Deserialize)]"
         )?;
+        writeln!(f, "#[reflect(Serialize, Deserialize)]")?;
         writeln!(f, "pub enum {} {{", enum_name)?;
 
         for entry in entries {
@@ -931,6 +1015,7 @@ fn generate_morph_enums(f: &mut File, assets_dir: &Path) -> io::Result<()> {
         f,
         "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]"
     )?;
+    writeln!(f, "#[reflect(Serialize, Deserialize)]")?;
     writeln!(f, "pub enum MorphTarget {{")?;
 
     for category in &category_names {
@@ -1316,6 +1401,26 @@ fn generate_expression_enum(f: &mut File, assets_dir: &Path) -> io::Result<()> {
     Ok(())
 }
 
+/// Asset path of a target.json target, searched in the other target folders when not in
+/// its category's (the measure targets live next to the body parts they measure)
+fn target_file(targets_dir: &Path, category: &str, target: &str) -> String {
+    let file = format!("{}.target", target);
+    let folder = match targets_dir.join(category).join(&file).exists() {
+        true => Some(category.to_string()),
+        false => fs::read_dir(targets_dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .find(|e| e.path().join(&file).exists())
+            .map(|e| e.file_name().to_string_lossy().into_owned()),
+    };
+    format!(
+        "make_human/targets/{}/{}",
+        folder.as_deref().unwrap_or(category),
+        file
+    )
+}
+
 /// Interpolation suffixes - last segment of filename that indicates min/avg/max
 const INTERP_SUFFIXES: &[(&str, &str)] = &[
     ("-minweight", "weight"),
@@ -1451,6 +1556,7 @@ fn generate_macro_morphs(f: &mut File, assets_dir: &Path) -> io::Result<()> {
         f,
         "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumCount, Reflect, Serialize, Deserialize)]"
     )?;
+    writeln!(f, "#[reflect(Serialize, Deserialize)]")?;
     writeln!(f, "pub enum MacroMorph {{")?;
 
     for morph in &all_morphs {
@@ -1588,6 +1694,14 @@ fn generate_mhpart_trait(f: &mut File) -> io::Result<()> {
             f,
             "    fn obj(&self) -> &'static str {{ self.get_str(\"obj\").unwrap() }}"
         )?;
+        writeln!(
+            f,
+            "    fn weights(&self, rig: &Rig) -> Option<&'static str> {{ self.get_str(&format!(\"mhw_{{:?}}\", rig)) }}"
+        )?;
+        writeln!(
+            f,
+            "    fn default_weights(&self) -> Option<&'static str> {{ self.get_str(\"mhw\") }}"
+        )?;
         writeln!(f, "}}")?;
         writeln!(f)?;
     }
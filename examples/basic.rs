@@ -58,10 +58,14 @@ fn setup(
             Clothing::ToigoMaleSuit3,
             Clothing::ToigoAnkleBootsMale,
         ]),
-        Morphs(vec![Morph::new(
-            MorphTarget::Macro(MacroMorph::CaucasianMaleYoung),
-            1.0,
-        )]),
+        MacroSliders {
+            gender: 1.0,
+            muscle: 0.7,
+            african: 0.0,
+            asian: 0.0,
+            caucasian: 1.0,
+            ..default()
+        },
         Transform::from_xyz(-1.0, 0.0, 0.0),
     ));
 
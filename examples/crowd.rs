@@ -0,0 +1,35 @@
+//! Spawn a grid of seeded random humans, same seeds give the same crowd
+use bevy::prelude::*;
+use bevy_make_human::prelude::*;
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins((DefaultPlugins, MakeHumanPlugin::default()))
+        .add_systems(Startup, setup)
+        .run()
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 4.0, -8.0).looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+    ));
+
+    commands.spawn((
+        DirectionalLight::default(),
+        Transform::from_xyz(4.0, 8.0, -4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    let generator = HumanGenerator {
+        age: age_slider(20.0)..=age_slider(70.0),
+        ..default()
+    };
+    for seed in 0..9u64 {
+        let position = Vec3::new((seed % 3) as f32 - 1.0, 0.0, (seed / 3) as f32 - 1.0) * 1.5;
+        let mut entity = commands.spawn((
+            Name::new(format!("Human {seed}")),
+            Transform::from_translation(position),
+        ));
+        generator.generate(seed).insert(&mut entity);
+    }
+}
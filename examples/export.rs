@@ -0,0 +1,44 @@
+//! Build a human and write it to a .glb, open it in Blender or any glTF viewer
+use bevy::prelude::*;
+use bevy_make_human::prelude::*;
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins((DefaultPlugins, MakeHumanPlugin::default()))
+        .add_systems(Startup, setup)
+        .add_observer(on_human_complete)
+        .run()
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Bob"),
+        Human,
+        Rig::Mixamo,
+        SkinMesh::MaleGeneric,
+        SkinMaterial::YoungCaucasianMale,
+        Eyes::LowPolyBluegreen,
+        Hair::CulturalibreHair02,
+        Eyebrows::Eyebrow006,
+        Eyelashes::Eyelashes01,
+        Teeth::TeethBase,
+        Tongue::Tongue01,
+        Outfit(vec![
+            Clothing::ToigoMaleSuit3,
+            Clothing::ToigoAnkleBootsMale,
+        ]),
+        Morphs(vec![Morph::new(
+            MorphTarget::Macro(MacroMorph::CaucasianMaleYoung),
+            1.0,
+        )]),
+    ));
+}
+
+fn on_human_complete(
+    trigger: On<HumanComplete>,
+    mut commands: Commands,
+    mut exit: MessageWriter<AppExit>,
+) {
+    commands.queue(ExportGlb::new(trigger.entity, "bob.glb"));
+    exit.write(AppExit::Success);
+}
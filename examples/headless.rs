@@ -0,0 +1,57 @@
+//! Dedicated server style setup, only skeleton, height and collider are built
+use bevy::{log::LogPlugin, prelude::*};
+use bevy_make_human::prelude::*;
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins((
+            MinimalPlugins,
+            LogPlugin::default(),
+            TransformPlugin,
+            MakeHumanPlugin {
+                headless: true,
+                ..default()
+            },
+        ))
+        .add_systems(Startup, setup)
+        .add_observer(on_human_complete)
+        .add_observer(on_human_failed)
+        .run()
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Bob"),
+        Human,
+        Rig::Mixamo,
+        SkinMesh::MaleGeneric,
+        SkinMaterial::YoungCaucasianMale,
+        Eyes::LowPolyBluegreen,
+        Eyebrows::Eyebrow006,
+        Eyelashes::Eyelashes01,
+        Teeth::TeethBase,
+        Tongue::Tongue01,
+    ));
+}
+
+fn on_human_complete(
+    trigger: On<HumanComplete>,
+    query: Query<(&Name, &HumanBuildCache)>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    if let Ok((name, cache)) = query.get(trigger.entity) {
+        info!(
+            "{} built: height {:.2}m, radius {:.2}m, {} bones",
+            name,
+            cache.height,
+            cache.radius,
+            cache.skeleton.bones.len()
+        );
+    }
+    exit.write(AppExit::Success);
+}
+
+fn on_human_failed(trigger: On<HumanFailed>, mut exit: MessageWriter<AppExit>) {
+    error!("{}", trigger.error);
+    exit.write(AppExit::error());
+}
@@ -0,0 +1,50 @@
+//! Morph a human to typed in measurements, then print what the build measured
+use bevy::prelude::*;
+use bevy_make_human::prelude::*;
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins((DefaultPlugins, MakeHumanPlugin::default()))
+        .add_systems(Startup, setup)
+        .add_systems(Update, print_measurements)
+        .run()
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 1.5, -3.0).looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+    ));
+
+    commands.spawn((
+        DirectionalLight::default(),
+        Transform::from_xyz(4.0, 8.0, -4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.spawn((
+        Name::new("Sam"),
+        Human,
+        SkinMesh::FemaleGeneric,
+        SkinMaterial::YoungCaucasianFemale,
+        MacroSliders {
+            gender: 0.0,
+            ..default()
+        },
+        MeasurementGoal {
+            height: Some(168.0),
+            chest: Some(92.0),
+            waist: Some(74.0),
+            hips: Some(100.0),
+            ..default()
+        },
+    ));
+}
+
+fn print_measurements(query: Query<(&Name, &HumanMeasurements), Changed<HumanMeasurements>>) {
+    for (name, m) in query.iter() {
+        info!(
+            "{}: height {:.1}cm, chest {:.1}cm, waist {:.1}cm, hips {:.1}cm, inseam {:.1}cm",
+            name, m.height, m.chest, m.waist, m.hips, m.inseam
+        );
+    }
+}
@@ -0,0 +1,29 @@
+//! Spawn a human from a .human.ron preset
+//!
+//! Run with `--features bevy/file_watcher` and edit assets/presets/bob.human.ron to hot reload
+use bevy::prelude::*;
+use bevy_make_human::prelude::*;
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins((DefaultPlugins, MakeHumanPlugin::default()))
+        .add_systems(Startup, setup)
+        .run()
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 1.5, -3.0).looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+    ));
+
+    commands.spawn((
+        DirectionalLight::default(),
+        Transform::from_xyz(4.0, 8.0, -4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.spawn((
+        Name::new("Bob"),
+        HumanPreset(asset_server.load("presets/bob.human.ron")),
+    ));
+}
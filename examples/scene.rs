@@ -0,0 +1,69 @@
+//! Save humans to a .scn.ron and load them back, they rebuild after loading
+//!
+//! First run spawns Bob and saves assets/scenes/humans.scn.ron, later runs load it
+use bevy::prelude::*;
+use bevy_make_human::prelude::*;
+
+const SCENE: &str = "scenes/humans.scn.ron";
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins((DefaultPlugins, MakeHumanPlugin::default()))
+        .add_systems(Startup, setup)
+        .add_observer(save_on_complete)
+        .run()
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 1.5, -3.0).looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+    ));
+    commands.spawn((
+        DirectionalLight::default(),
+        Transform::from_xyz(4.0, 8.0, -4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    if std::path::Path::new("assets").join(SCENE).exists() {
+        info!("Loading {}", SCENE);
+        commands.spawn(DynamicSceneRoot(asset_server.load(SCENE)));
+        return;
+    }
+
+    commands.spawn((
+        Name::new("Bob"),
+        Human,
+        Rig::Mixamo,
+        SkinMesh::MaleGeneric,
+        SkinMaterial::YoungCaucasianMale,
+        Eyes::LowPolyBluegreen,
+        Hair::CulturalibreHair02,
+        Outfit(vec![Clothing::ToigoMaleSuit3]),
+        Morphs(vec![Morph::new(
+            MorphTarget::Macro(MacroMorph::CaucasianMaleYoung),
+            1.0,
+        )]),
+    ));
+}
+
+fn save_on_complete(_trigger: On<HumanComplete>, mut commands: Commands) {
+    commands.queue(|world: &mut World| {
+        let humans: Vec<Entity> = world
+            .query_filtered::<Entity, With<Human>>()
+            .iter(world)
+            .collect();
+        let scene = human_scene(world, humans);
+        let registry = world.resource::<AppTypeRegistry>().read();
+        match scene.serialize(&registry) {
+            Ok(ron) => {
+                let path = std::path::Path::new("assets").join(SCENE);
+                if let Err(e) = std::fs::create_dir_all(path.parent().unwrap())
+                    .and_then(|_| std::fs::write(&path, ron))
+                {
+                    error!("Failed to write {:?}: {}", path, e);
+                }
+            }
+            Err(e) => error!("Failed to serialize scene: {}", e),
+        }
+    });
+}
@@ -19,25 +19,75 @@ pub trait MHPart: MHThumb {
     fn mhclo(&self) -> &'static str;
     fn mhmat(&self) -> &'static str;
     fn obj(&self) -> &'static str;
+    /// Own vertex bone weights made for `rig`, a `<name>.<rig>.mhw` next to the mhclo
+    fn weights(&self, rig: &Rig) -> Option<&'static str>;
+    /// Own vertex bone weights made for [`REFERENCE_RIG`], any other `.mhw` next to the mhclo
+    fn default_weights(&self) -> Option<&'static str>;
+}
+
+/// Rig MakeHuman's own `.mhw` and mhclo `vertexboneweights_file` weights are made for
+pub const REFERENCE_RIG: Rig = Rig::Mh;
+
+/// A part's own vertex bone weights file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PartWeightsFile {
+    /// Made for the human's rig
+    Rig(&'static str),
+    /// Made for [`REFERENCE_RIG`], remapped onto the human's rig
+    Reference(&'static str),
+}
+
+impl PartWeightsFile {
+    pub fn path(&self) -> &'static str {
+        match self {
+            PartWeightsFile::Rig(path) | PartWeightsFile::Reference(path) => path,
+        }
+    }
 }
 
 /// Converts Enums to Handles
 #[derive(Component)]
 #[component(storage = "SparseSet")]
 pub struct HumanAssets {
-    pub skin_obj_base: Handle<ObjBaseMesh>,
-    pub skin_proxy: Handle<ProxyAsset>,
-    pub skin_material: Handle<StandardMaterial>,
+    /// Generation this build was requested as, see [`HumanGeneration`]
+    pub generation: u64,
+    /// Body assets, None when only parts changed and the last built body is reused
+    pub body: Option<HumanBodyAssets>,
+    pub rig_weights: Handle<SkinningWeights>,
+    /// [`REFERENCE_RIG`] weights when the human uses another rig, to remap part weights
+    pub reference_weights: Option<Handle<SkinningWeights>>,
 
     pub parts: Vec<MHItem>,
+    /// Every worn clothing item, their delete_verts hide the skin underneath
+    pub clothes: Vec<Handle<MhcloAsset>>,
+
+    /// Offset to push clothing outward (prevents skin poke-through)
+    pub clothing_offset: f32,
+    /// Joint influence limit the skin and parts are skinned with
+    pub influences: SkinInfluences,
+    /// Part entities from the last build that this build replaces
+    pub stale_parts: Vec<Entity>,
+}
+
+/// Assets needed to morph the base mesh, build the skeleton and fit the skin
+pub struct HumanBodyAssets {
+    /// Components these handles were loaded from
+    pub source: HumanBodySource,
+
+    /// Skin proxy and material, None in headless mode
+    pub skin: Option<HumanSkinAssets>,
 
     pub rig_bones: Handle<RigBones>,
-    pub rig_weights: Handle<SkinningWeights>,
 
     /// All morph targets (body morphs + macro morphs)
     pub morphs: Vec<(Handle<MorphTargetData>, f32)>,
-    /// Offset to push clothing outward (prevents skin poke-through)
-    pub clothing_offset: f32,
+}
+
+/// Assets needed to fit and shade the skin
+pub struct HumanSkinAssets {
+    pub obj_base: Handle<ObjBaseMesh>,
+    pub proxy: Handle<ProxyAsset>,
+    pub material: Handle<StandardMaterial>,
 
     #[cfg(feature = "arkit")]
     /// ARKit blend shape targets (52 shapes)
@@ -47,70 +97,126 @@ pub struct HumanAssets {
 impl HumanAssets {
     /// Get all handles for progress tracking
     pub fn all_handles(&self) -> Vec<UntypedHandle> {
-        let mut handles = vec![
-            self.skin_obj_base.clone().untyped(),
-            self.skin_proxy.clone().untyped(),
-            self.skin_material.clone().untyped(),
-            self.rig_bones.clone().untyped(),
-            self.rig_weights.clone().untyped(),
-        ];
+        let mut handles = vec![self.rig_weights.clone().untyped()];
+        handles.extend(self.reference_weights.clone().map(|h| h.untyped()));
 
-        for part in &self.parts {
-            handles.extend(part.handles());
+        if let Some(body) = &self.body {
+            handles.push(body.rig_bones.clone().untyped());
+
+            for (handle, _value) in &body.morphs {
+                handles.push(handle.clone().untyped());
+            }
+
+            if let Some(skin) = &body.skin {
+                handles.extend([
+                    skin.obj_base.clone().untyped(),
+                    skin.proxy.clone().untyped(),
+                    skin.material.clone().untyped(),
+                ]);
+
+                #[cfg(feature = "arkit")]
+                for handle in &skin.arkit_targets {
+                    handles.push(handle.clone().untyped());
+                }
+            }
         }
 
-        for (handle, _value) in &self.morphs {
-            handles.push(handle.clone().untyped());
+        for part in &self.parts {
+            handles.extend(part.handles());
         }
 
-        #[cfg(feature = "arkit")]
-        for handle in &self.arkit_targets {
-            handles.push(handle.clone().untyped());
+        for clo in &self.clothes {
+            handles.push(clo.clone().untyped());
         }
 
         handles
     }
 }
 
-pub struct MHItem {
+/// Files a part is built from, also used to skip unchanged parts on rebuild
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MHPartSource {
     pub tag: MHTag,
+    pub mhclo: &'static str,
+    pub mhmat: &'static str,
+    pub obj: &'static str,
+    /// Own weights for the human's rig, the mhclo's `vertexboneweights_file` is used without
+    pub weights: Option<PartWeightsFile>,
+}
+
+impl MHPartSource {
+    pub fn new<T: MHPart>(tag: MHTag, part: &T, rig: Rig) -> Self {
+        let weights = part
+            .weights(&rig)
+            .map(PartWeightsFile::Rig)
+            .or(part.default_weights().map(PartWeightsFile::Reference));
+        Self {
+            tag,
+            mhclo: part.mhclo(),
+            mhmat: part.mhmat(),
+            obj: part.obj(),
+            weights,
+        }
+    }
+}
+
+pub struct MHItem {
+    pub source: MHPartSource,
     pub clo: Handle<MhcloAsset>,
     pub mat: Handle<StandardMaterial>,
     pub obj_base: Handle<ObjBaseMesh>, // Mesh + original verts for mhid_lookup
+    /// See [`MHPartSource::weights`]
+    pub weights: Option<Handle<SkinningWeights>>,
 }
 
 impl MHItem {
     /// Load assets (clo, mat, obj with verts)
-    pub fn load<T: MHPart>(tag: MHTag, part: &T, asset_server: &AssetServer) -> Self {
+    pub fn load<T: MHPart>(tag: MHTag, part: &T, rig: Rig, asset_server: &AssetServer) -> Self {
+        Self::load_source(MHPartSource::new(tag, part, rig), asset_server)
+    }
+
+    /// Load assets for a part source
+    pub fn load_source(source: MHPartSource, asset_server: &AssetServer) -> Self {
         Self {
-            tag,
-            clo: asset_server.load(part.mhclo().to_string()),
-            mat: asset_server.load(part.mhmat().to_string()),
-            obj_base: asset_server.load(part.obj().to_string()),
+            source,
+            clo: asset_server.load(source.mhclo.to_string()),
+            mat: asset_server.load(source.mhmat.to_string()),
+            obj_base: asset_server.load(source.obj.to_string()),
+            weights: source
+                .weights
+                .map(|w| asset_server.load(w.path().to_string())),
         }
     }
 
     /// Get all handles for this item
     pub fn handles(&self) -> Vec<UntypedHandle> {
-        vec![
+        let mut handles = vec![
             self.clo.clone().untyped(),
             self.mat.clone().untyped(),
             self.obj_base.clone().untyped(),
-        ]
+        ];
+        handles.extend(self.weights.clone().map(|h| h.untyped()));
+        handles
     }
 }
 
 pub struct MHItemLoaded {
-    pub tag: MHTag,
+    pub source: MHPartSource,
     pub mat: Handle<StandardMaterial>, // dont do anything currently with material, but we need pass it along
     pub clo: MhcloAsset,
     pub base: ObjBaseMesh,
+    /// The part's own vertex bone weights, see [`MHPartSource::weights`] and [`MhcloAsset::vertex_bone_weights`]
+    pub weights: Option<SkinningWeights>,
+    /// `weights` use [`REFERENCE_RIG`] bone names
+    pub reference_weights: bool,
 }
 
 pub struct MHItemResult {
-    pub tag: MHTag,
+    pub source: MHPartSource,
     pub mat: Handle<StandardMaterial>, // dont do anything currently with material, but we need pass it along
     pub mesh: Mesh,
+    /// Material depth bias from the clothing layer, see [`crate::bake::part_depth_bias`]
+    pub depth_bias: f32,
 }
 
 pub struct MHItemFinal {
@@ -122,8 +228,8 @@ pub struct MHItemFinal {
 /// A morph target with a value
 /// - Binary morphs (body parts): -1.0 to 1.0 (neg=decr, pos=incr)
 /// - Single/Macro morphs: 0.0 to 1.0
-#[derive(Debug, Clone, Reflect, InspectorOptions)]
-#[reflect(InspectorOptions)]
+#[derive(Debug, Clone, PartialEq, Reflect, InspectorOptions, Serialize, Deserialize)]
+#[reflect(InspectorOptions, Serialize, Deserialize)]
 pub struct Morph {
     pub target: MorphTarget,
     #[inspector(min = -1.0, max = 1.0, speed = 0.01, display = NumberDisplay::Slider)]
@@ -150,3 +256,104 @@ impl From<(MorphTarget, f32)> for Morph {
         Self::new(target, value)
     }
 }
+
+/// Ethnic slider order, see [`ethnic_macro_morphs`]
+pub const ETHNICITIES: [&str; 3] = ["African", "Asian", "Caucasian"];
+pub(crate) const AGES: [&str; 4] = ["baby", "child", "young", "old"];
+/// Age slider value of each age target, 1, 11, 25 and 90 years
+const AGE_ANCHORS: [f32; 4] = [0.0, 0.1875, 0.5, 1.0];
+const AGE_YEARS: [f32; 4] = [1.0, 11.0, 25.0, 90.0];
+
+/// MakeHuman age slider (0..1) for an age in years
+pub fn age_slider(years: f32) -> f32 {
+    let years = years.clamp(AGE_YEARS[0], AGE_YEARS[3]);
+    let i = AGE_YEARS
+        .windows(2)
+        .position(|w| years <= w[1])
+        .unwrap_or(2);
+    let t = (years - AGE_YEARS[i]) / (AGE_YEARS[i + 1] - AGE_YEARS[i]);
+    AGE_ANCHORS[i] + t * (AGE_ANCHORS[i + 1] - AGE_ANCHORS[i])
+}
+
+/// Weights of the baby, child, young and old targets for an age slider value
+pub(crate) fn age_weights(age: f32) -> [f32; 4] {
+    let age = age.clamp(0.0, 1.0);
+    let mut weights = [0.0; 4];
+    let i = AGE_ANCHORS
+        .windows(2)
+        .position(|w| age <= w[1])
+        .unwrap_or(2);
+    let t = (age - AGE_ANCHORS[i]) / (AGE_ANCHORS[i + 1] - AGE_ANCHORS[i]);
+    weights[i] = 1.0 - t;
+    weights[i + 1] = t;
+    weights
+}
+
+/// `<ethnic>-<gender>-<age>` macro targets with (ethnic, is male, age) indices
+fn ethnic_macros() -> impl Iterator<Item = (MacroMorph, usize, bool, usize)> {
+    MacroMorph::iter().filter_map(|m| {
+        let (None, Some(path), None) = m.paths() else {
+            return None;
+        };
+        let stem = std::path::Path::new(path).file_stem()?.to_str()?;
+        let mut parts = stem.split('-');
+        let ethnic = parts.next()?;
+        let e = ETHNICITIES
+            .iter()
+            .position(|e| e.eq_ignore_ascii_case(ethnic))?;
+        let male = match parts.next()? {
+            "male" => true,
+            "female" => false,
+            _ => return None,
+        };
+        let age = AGES.iter().position(|a| Some(*a) == parts.next())?;
+        Some((m, e, male, age))
+    })
+}
+
+/// Morph set by [`ethnic_macro_morphs`]
+pub fn is_ethnic_macro(target: &MorphTarget) -> bool {
+    let MorphTarget::Macro(m) = target else {
+        return false;
+    };
+    ethnic_macros().any(|(mm, ..)| mm == *m)
+}
+
+/// MakeHuman's gender (0 female, 1 male), age and ethnic sliders as `<ethnic>-<gender>-<age>` macro morphs
+///
+/// Ethnic values are normalized, see [`ETHNICITIES`] for the order
+pub fn ethnic_macro_morphs(gender: f32, age: f32, ethnic: [f32; 3]) -> Vec<Morph> {
+    let gender = gender.clamp(0.0, 1.0);
+    let ages = age_weights(age);
+    let sum: f32 = ethnic.iter().sum();
+    let ethnic = match sum > 0.0 {
+        true => ethnic.map(|e| e / sum),
+        false => [1.0 / 3.0; 3],
+    };
+    ethnic_macros()
+        .filter_map(|(morph, e, male, age)| {
+            let gender = if male { gender } else { 1.0 - gender };
+            let weight = ethnic[e] * gender * ages[age];
+            (weight > 0.001).then(|| Morph::macro_morph(morph, weight))
+        })
+        .collect()
+}
+
+/// Gender, age and ethnic sliders back from [`ethnic_macro_morphs`], None without any of its morphs
+pub fn ethnic_macro_sliders(morphs: &[Morph]) -> Option<(f32, f32, [f32; 3])> {
+    let macros: Vec<_> = ethnic_macros().collect();
+    let (mut total, mut gender, mut age) = (0.0, 0.0, 0.0);
+    let mut ethnic = [0.0; 3];
+    for morph in morphs {
+        let MorphTarget::Macro(m) = morph.target else {
+            continue;
+        };
+        if let Some(&(_, e, male, a)) = macros.iter().find(|(mm, ..)| *mm == m) {
+            total += morph.value;
+            gender += if male { morph.value } else { 0.0 };
+            age += AGE_ANCHORS[a] * morph.value;
+            ethnic[e] += morph.value;
+        }
+    }
+    (total > 0.0).then(|| (gender / total, age / total, ethnic.map(|e| e / total)))
+}
@@ -0,0 +1,492 @@
+//! Build humans from plain loaded data, no App or asset server needed
+//!
+//! Same steps the plugin runs in its async build task, for tools, tests and offline bakers
+
+use bevy::{mesh::Indices, prelude::*};
+
+use crate::{
+    components::{MHTag, SkinInfluences},
+    loaders::*,
+    measure::{HumanMeasurements, body_vertices, measure_body},
+    skeleton::Skeleton,
+    util::*,
+};
+
+/// Extra push along normals per clothing layer, so outer layers clear inner ones
+pub const CLOTHING_LAYER_OFFSET: f32 = 0.001;
+
+/// Material depth bias per clothing layer, so outer layers win depth ties with inner ones
+pub const CLOTHING_LAYER_DEPTH_BIAS: f32 = 1.0;
+
+/// Everything needed to bake a human
+pub struct BakeInput {
+    /// Base mesh vertices, see [`crate::BaseMesh`]
+    pub base_vertices: Vec<Vec3>,
+    pub base_vertex_groups: VertexGroups,
+    /// Morph targets and weights (body morphs + macro morphs)
+    pub morphs: Vec<(MorphTargetData, f32)>,
+    pub rig: RigBones,
+    pub skinning_weights: SkinningWeights,
+    /// Skin proxy, None to skip the skin mesh
+    pub skin: Option<BakeSkin>,
+    pub parts: Vec<BakePart>,
+    /// Pushes clothing outward, see [`crate::components::ClothingOffset`]
+    /// Each clothing layer adds [`CLOTHING_LAYER_OFFSET`] on top
+    pub clothing_offset: f32,
+    /// Joint influence limit for the skin and all parts
+    pub influences: SkinInfluences,
+}
+
+pub struct BakeSkin {
+    pub proxy: ProxyAsset,
+    pub obj_base: ObjBaseMesh,
+    #[cfg(feature = "arkit")]
+    /// ARKit blend shape targets (52 shapes), empty to skip
+    pub arkit_targets: Vec<MorphTargetData>,
+}
+
+/// Hair, eyes, clothes, etc
+pub struct BakePart {
+    pub tag: MHTag,
+    pub clo: MhcloAsset,
+    pub obj_base: ObjBaseMesh,
+    /// The part's own vertex bone weights in the rig's bone names, see [`MhcloAsset::vertex_bone_weights`]
+    /// and [`SkinningWeights::remap_bones`] for weights made for another rig
+    pub weights: Option<SkinningWeights>,
+}
+
+pub struct BakedHuman {
+    pub body: BakedBody,
+    pub skin: Option<BakedSkin>,
+    /// Fitted and skinned part meshes, in input order
+    pub parts: Vec<(MHTag, Mesh)>,
+}
+
+/// Morphed base mesh, skeleton and body dimensions
+#[derive(Clone)]
+pub struct BakedBody {
+    /// Base mesh vertices with all morphs applied
+    pub morphed_vertices: Vec<Vec3>,
+    pub skeleton: Skeleton,
+    /// Height (max_y - min_y of morphed vertices)
+    pub height: f32,
+    /// Min Y of morphed vertices (for ground offset)
+    pub min_y: f32,
+    /// Horizontal torso radius of the body
+    pub radius: f32,
+    pub measurements: HumanMeasurements,
+}
+
+pub struct BakedSkin {
+    /// Fitted skin proxy with joint indices and weights
+    pub mesh: Mesh,
+    /// All skin triangles, for hiding skin under clothing
+    pub mask: SkinMask,
+    #[cfg(feature = "arkit")]
+    /// ARKit morph deltas transferred to proxy mesh (52 x vertex_count)
+    pub arkit_morphs: Vec<Vec<Vec3>>,
+}
+
+/// Skin triangles before any are hidden, and the base mesh vertex each skin vertex follows
+///
+/// Lets the skin be re-masked when clothing changes without refitting it
+#[derive(Clone, Debug, Default)]
+pub struct SkinMask {
+    /// Skin mesh triangle list
+    pub triangles: Vec<u32>,
+    /// Base mesh vertices per skin mesh vertex, its binding triangle with `u32::MAX` for unweighted corners
+    pub base_vertices: Vec<[u32; 3]>,
+}
+
+impl SkinMask {
+    pub fn new(mesh: &Mesh, proxy: &ProxyAsset, mhid_lookup: &[u16]) -> Self {
+        let triangles = mesh
+            .indices()
+            .map(|i| i.iter().map(|i| i as u32).collect())
+            .unwrap_or_default();
+        let base_vertices = mhid_lookup
+            .iter()
+            .map(|&obj_idx| match proxy.bindings.get(obj_idx as usize) {
+                Some(b) => std::array::from_fn(|i| match b.weights[i] != 0.0 {
+                    true => b.triangle[i],
+                    false => u32::MAX,
+                }),
+                None => [u32::MAX; 3],
+            })
+            .collect();
+        Self {
+            triangles,
+            base_vertices,
+        }
+    }
+
+    /// Triangles left after dropping any with a skin vertex bound to a `deleted` base mesh vertex, `deleted` is sorted
+    pub fn visible_triangles(&self, deleted: &[u32]) -> Vec<u32> {
+        if deleted.is_empty() {
+            return self.triangles.clone();
+        }
+        let hidden: Vec<bool> = self
+            .base_vertices
+            .iter()
+            .map(|tri| tri.iter().any(|v| deleted.binary_search(v).is_ok()))
+            .collect();
+        self.triangles
+            .chunks_exact(3)
+            .filter(|tri| {
+                !tri.iter()
+                    .any(|&i| hidden.get(i as usize).copied().unwrap_or(false))
+            })
+            .flatten()
+            .copied()
+            .collect()
+    }
+}
+
+impl BakedSkin {
+    /// Hide skin under clothing, see [`deleted_vertices`]
+    pub fn hide_vertices(&mut self, deleted: &[u32]) {
+        self.mesh
+            .insert_indices(Indices::U32(self.mask.visible_triangles(deleted)));
+    }
+}
+
+/// Sorted base mesh vertices hidden by any of `clothes`, see [`MhcloAsset::delete_verts`]
+pub fn deleted_vertices<'a>(clothes: impl IntoIterator<Item = &'a MhcloAsset>) -> Vec<u32> {
+    let mut deleted: Vec<u32> = clothes
+        .into_iter()
+        .flat_map(|clo| clo.delete_verts.iter().copied())
+        .collect();
+    deleted.sort_unstable();
+    deleted.dedup();
+    deleted
+}
+
+/// Sorted distinct z_depths of `clothes`, a clothing item's layer is its index in here
+pub fn clothing_layers<'a>(clothes: impl IntoIterator<Item = &'a MhcloAsset>) -> Vec<u32> {
+    let mut layers: Vec<u32> = clothes.into_iter().map(|clo| clo.z_depth).collect();
+    layers.sort_unstable();
+    layers.dedup();
+    layers
+}
+
+/// Index of a clothing item's z_depth in `layers`, 0 is the innermost
+pub fn clothing_layer(clo: &MhcloAsset, layers: &[u32]) -> usize {
+    layers.partition_point(|&z| z < clo.z_depth)
+}
+
+/// Normal push for a part, clothing layers stack outward from `clothing_offset`
+pub fn part_offset(tag: MHTag, clo: &MhcloAsset, layers: &[u32], clothing_offset: f32) -> f32 {
+    match tag {
+        MHTag::Clothes => {
+            clothing_offset + clothing_layer(clo, layers) as f32 * CLOTHING_LAYER_OFFSET
+        }
+        _ => 0.0,
+    }
+}
+
+/// Material depth bias for a part, clothing layers stack outward like [`part_offset`]
+pub fn part_depth_bias(tag: MHTag, clo: &MhcloAsset, layers: &[u32]) -> f32 {
+    match tag {
+        MHTag::Clothes => clothing_layer(clo, layers) as f32 * CLOTHING_LAYER_DEPTH_BIAS,
+        _ => 0.0,
+    }
+}
+
+/// Morph, build the skeleton, then fit and skin the skin and all parts
+pub fn bake_human(input: &BakeInput) -> BakedHuman {
+    let body = bake_body(
+        &input.base_vertices,
+        &input.base_vertex_groups,
+        &input.morphs,
+        &input.rig,
+    );
+    let clothes = || {
+        input
+            .parts
+            .iter()
+            .filter(|p| p.tag == MHTag::Clothes)
+            .map(|p| &p.clo)
+    };
+    let deleted = deleted_vertices(clothes());
+    let layers = clothing_layers(clothes());
+    let skin = input.skin.as_ref().map(|skin| {
+        let mut skin = bake_skin(skin, &body, &input.skinning_weights, &input.influences);
+        skin.hide_vertices(&deleted);
+        skin
+    });
+    let parts = input
+        .parts
+        .iter()
+        .map(|p| {
+            let offset = part_offset(p.tag, &p.clo, &layers, input.clothing_offset);
+            let mesh = bake_part(
+                &p.clo,
+                &p.obj_base,
+                &body,
+                &input.skinning_weights,
+                p.weights.as_ref(),
+                &input.influences,
+                offset,
+            );
+            (p.tag, mesh)
+        })
+        .collect();
+
+    BakedHuman { body, skin, parts }
+}
+
+/// Apply weighted morph targets to the base mesh vertices
+pub fn morph_vertices<'a>(
+    base_vertices: &[Vec3],
+    morphs: impl IntoIterator<Item = (&'a MorphTargetData, f32)>,
+) -> Vec<Vec3> {
+    let mut morphed_vertices = base_vertices.to_vec();
+    for (morph_data, weight) in morphs {
+        if weight < 0.001 {
+            continue;
+        }
+        for (&mh_idx, &offset) in &morph_data.offsets {
+            let idx = mh_idx as usize;
+            if idx < morphed_vertices.len() {
+                morphed_vertices[idx] += offset * weight;
+            }
+        }
+    }
+    morphed_vertices
+}
+
+/// Morph the base mesh, build the skeleton and measure the body
+pub fn bake_body(
+    base_vertices: &[Vec3],
+    base_vertex_groups: &VertexGroups,
+    morphs: &[(MorphTargetData, f32)],
+    rig: &RigBones,
+) -> BakedBody {
+    let morphed_vertices = morph_vertices(base_vertices, morphs.iter().map(|(m, w)| (m, *w)));
+    let skeleton = rig.build_skeleton(&morphed_vertices, base_vertex_groups);
+
+    // Calculate human height from morphed vertices
+    let (min_y, max_y) = morphed_vertices
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(min, max), v| {
+            (min.min(v.y), max.max(v.y))
+        });
+    let height = max_y - min_y;
+
+    // Measure the body only, helpers (tights, skirt, hair) sit outside it
+    let body_vertices = body_vertices(&morphed_vertices, base_vertex_groups);
+    let radius = torso_radius(&body_vertices, min_y, height).unwrap_or(0.25);
+    let measurements = measure_body(&morphed_vertices, &body_vertices);
+
+    BakedBody {
+        morphed_vertices,
+        skeleton,
+        height,
+        min_y,
+        radius,
+        measurements,
+    }
+}
+
+/// Fit and skin the skin proxy to the morphed body
+pub fn bake_skin(
+    skin: &BakeSkin,
+    body: &BakedBody,
+    skinning_weights: &SkinningWeights,
+    influences: &SkinInfluences,
+) -> BakedSkin {
+    let (proxy_asset, proxy_obj) = (&skin.proxy, &skin.obj_base);
+    let mut mesh = apply_proxy_fitting(
+        &proxy_obj.mesh,
+        proxy_asset,
+        &body.morphed_vertices,
+        &proxy_obj.vertices,
+    );
+    mesh = apply_skinning_weights_to_proxy(
+        mesh,
+        proxy_asset,
+        &proxy_obj.mhid_lookup,
+        &body.skeleton,
+        skinning_weights,
+        influences,
+    );
+    let mask = SkinMask::new(&mesh, proxy_asset, &proxy_obj.mhid_lookup);
+
+    #[cfg(feature = "arkit")]
+    let arkit_morphs: Vec<Vec<Vec3>> = skin
+        .arkit_targets
+        .iter()
+        .map(|morph_data| {
+            proxy_obj
+                .mhid_lookup
+                .iter()
+                .map(|&obj_idx| {
+                    let binding = &proxy_asset.bindings[obj_idx as usize];
+                    let d0 = morph_data
+                        .offsets
+                        .get(&binding.triangle[0])
+                        .copied()
+                        .unwrap_or(Vec3::ZERO);
+                    let d1 = morph_data
+                        .offsets
+                        .get(&binding.triangle[1])
+                        .copied()
+                        .unwrap_or(Vec3::ZERO);
+                    let d2 = morph_data
+                        .offsets
+                        .get(&binding.triangle[2])
+                        .copied()
+                        .unwrap_or(Vec3::ZERO);
+                    // Barycentric interpolation of offsets
+                    d0 * binding.weights[0] + d1 * binding.weights[1] + d2 * binding.weights[2]
+                })
+                .collect()
+        })
+        .collect();
+
+    BakedSkin {
+        mesh,
+        mask,
+        #[cfg(feature = "arkit")]
+        arkit_morphs,
+    }
+}
+
+/// Fit a part to the morphed body and skin it, `offset` pushes it out along normals
+///
+/// `own_weights` are the part's own vertex bone weights, body weights fill in where they don't fit the rig
+pub fn bake_part(
+    clo: &MhcloAsset,
+    obj_base: &ObjBaseMesh,
+    body: &BakedBody,
+    skinning_weights: &SkinningWeights,
+    own_weights: Option<&SkinningWeights>,
+    influences: &SkinInfluences,
+    offset: f32,
+) -> Mesh {
+    let mesh = apply_mhclo_fitting(
+        &obj_base.mesh,
+        clo,
+        &obj_base.mhid_lookup,
+        &body.morphed_vertices,
+        offset,
+    );
+    apply_skinning_weights_via_mhclo(
+        mesh,
+        clo,
+        &obj_base.mhid_lookup,
+        &body.skeleton,
+        skinning_weights,
+        own_weights,
+        influences,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{asset::RenderAssetUsages, mesh::PrimitiveTopology};
+
+    fn clothing(z_depth: u32) -> MhcloAsset {
+        MhcloAsset {
+            z_depth,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn test_delete_verts_hide_skin() {
+        // skin triangles 0-1-2 and 1-3-4, vertex 2 leans a little on base vertex 14
+        let mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_indices(Indices::U32(vec![0, 1, 2, 1, 3, 4]));
+        let binding = |triangle, weights| VertexBinding {
+            triangle,
+            weights,
+            offset: Vec3::ZERO,
+        };
+        let proxy = ProxyAsset {
+            bindings: vec![
+                binding([10, 11, 12], [1.0, 0.0, 0.0]),
+                binding([11, 12, 13], [0.5, 0.3, 0.2]),
+                binding([12, 13, 14], [0.6, 0.3, 0.1]),
+                binding([20, 21, 22], [0.6, 0.4, 0.0]),
+                binding([20, 21, 23], [0.2, 0.4, 0.4]),
+            ],
+            ..default()
+        };
+        let mask = SkinMask::new(&mesh, &proxy, &[0, 1, 2, 3, 4]);
+
+        let garment = |delete_verts| MhcloAsset {
+            delete_verts,
+            ..default()
+        };
+        let shirt = garment(vec![14]);
+        let belt = garment(vec![22]);
+        assert_eq!(
+            mask.visible_triangles(&deleted_vertices([&shirt])),
+            vec![1, 3, 4]
+        );
+        // a corner with no weight doesn't hide the vertex
+        assert_eq!(
+            mask.visible_triangles(&deleted_vertices([&belt])),
+            vec![0, 1, 2, 1, 3, 4]
+        );
+        assert!(
+            mask.visible_triangles(&deleted_vertices([&shirt, &garment(vec![23])]))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_clothing_layers_order() {
+        let (shirt, jacket, socks) = (clothing(50), clothing(70), clothing(10));
+        let layers = clothing_layers([&jacket, &shirt, &socks, &clothing(50)]);
+        assert_eq!(layers, vec![10, 50, 70]);
+
+        let bias = |clo| part_depth_bias(MHTag::Clothes, clo, &layers);
+        assert_eq!(bias(&socks), 0.0);
+        assert!(bias(&socks) < bias(&shirt) && bias(&shirt) < bias(&jacket));
+        assert_eq!(bias(&jacket), 2.0 * CLOTHING_LAYER_DEPTH_BIAS);
+        // body parts keep their material as is
+        assert_eq!(part_depth_bias(MHTag::Hair, &jacket, &layers), 0.0);
+        assert!(
+            part_offset(MHTag::Clothes, &shirt, &layers, 0.0)
+                < part_offset(MHTag::Clothes, &jacket, &layers, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_morph_vertices() {
+        let base = [Vec3::ZERO, Vec3::ONE];
+        let grow = MorphTargetData {
+            offsets: [(0, Vec3::Y), (7, Vec3::X)].into_iter().collect(),
+        };
+        let shift = MorphTargetData {
+            offsets: [(1, Vec3::X)].into_iter().collect(),
+        };
+        let morphed = morph_vertices(&base, [(&grow, 0.5), (&shift, 2.0)]);
+        assert_eq!(
+            morphed,
+            vec![Vec3::new(0.0, 0.5, 0.0), Vec3::new(3.0, 1.0, 1.0)]
+        );
+        // weights below 0.001 are skipped
+        assert_eq!(morph_vertices(&base, [(&grow, 0.0005)]), base.to_vec());
+    }
+
+    #[test]
+    fn test_deleted_vertices_sorted() {
+        let shirt = MhcloAsset {
+            delete_verts: vec![9, 3, 5],
+            ..default()
+        };
+        let jacket = MhcloAsset {
+            delete_verts: vec![5, 1],
+            ..default()
+        };
+        assert_eq!(deleted_vertices([&shirt, &jacket]), vec![1, 3, 5, 9]);
+        assert!(deleted_vertices([]).is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+//! On-disk cache for prepared basemesh data
+//!
+//! Format (little endian):
+//! - magic `MHBC`
+//! - format version (u32)
+//! - source hash (u64), see [`ObjBaseMesh::hash`](crate::loaders::ObjBaseMesh)
+//! - entry count (u32)
+//! - entries (u16 each)
+
+use bevy::prelude::*;
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+const MAGIC: &[u8; 4] = b"MHBC";
+/// Bump when the layout or the generation of cached data changes
+const VERSION: u32 = 1;
+
+/// File name of the cached basemesh mhid_lookup inside [`MHCache::dir`]
+pub const BASEMESH_CACHE_FILE: &str = "basemesh.mhid";
+
+/// Where prepared data is cached between launches, `None` disables caching
+#[derive(Resource, Clone, Debug, Default)]
+pub struct MHCache {
+    pub dir: Option<PathBuf>,
+}
+
+impl MHCache {
+    /// Full path for a cache file, if caching is enabled
+    pub fn file(&self, name: &str) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(name))
+    }
+}
+
+/// Stable 64-bit FNV-1a hash, used to key cache files on source contents
+pub fn hash_bytes(bytes: &[u8]) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes
+        .iter()
+        .fold(OFFSET, |hash, &b| (hash ^ b as u64).wrapping_mul(PRIME))
+}
+
+/// Read a cached mhid_lookup, returns None if missing, corrupt or stale
+pub fn read_mhid_lookup(path: &Path, hash: u64) -> Option<Vec<u16>> {
+    let bytes = fs::read(path).ok()?;
+    if bytes.len() < 20 || &bytes[0..4] != MAGIC {
+        return None;
+    }
+    let version = u32::from_le_bytes(bytes[4..8].try_into().ok()?);
+    let stored_hash = u64::from_le_bytes(bytes[8..16].try_into().ok()?);
+    if version != VERSION || stored_hash != hash {
+        return None;
+    }
+    let count = u32::from_le_bytes(bytes[16..20].try_into().ok()?) as usize;
+    let data = &bytes[20..];
+    if data.len() != count * 2 {
+        return None;
+    }
+    Some(
+        data.chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect(),
+    )
+}
+
+/// Write mhid_lookup to cache, creating parent dirs as needed
+pub fn write_mhid_lookup(path: &Path, hash: u64, lookup: &[u16]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut bytes = Vec::with_capacity(20 + lookup.len() * 2);
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&hash.to_le_bytes());
+    bytes.extend_from_slice(&(lookup.len() as u32).to_le_bytes());
+    for v in lookup {
+        bytes.extend_from_slice(&v.to_le_bytes());
+    }
+
+    // write to temp file first so a crash never leaves a half written cache
+    let tmp = temp_path(path);
+    let written = fs::File::create(&tmp)
+        .and_then(|mut f| f.write_all(&bytes))
+        .and_then(|_| fs::rename(&tmp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
+}
+
+/// Temp file next to `path`, unique per process and call so apps sharing a cache dir never write the same one
+fn temp_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_extension(format!("{}-{}.tmp", std::process::id(), n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mhid_cache_roundtrip() {
+        let path = std::env::temp_dir()
+            .join(format!("bevy_make_human_test_{}", std::process::id()))
+            .join(BASEMESH_CACHE_FILE);
+        let lookup = vec![0u16, 5, 5, 2, 65535];
+
+        write_mhid_lookup(&path, 42, &lookup).unwrap();
+        assert_eq!(read_mhid_lookup(&path, 42), Some(lookup));
+        // different source hash is stale
+        assert_eq!(read_mhid_lookup(&path, 43), None);
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_temp_path_unique() {
+        let path = Path::new("cache").join(BASEMESH_CACHE_FILE);
+        let (a, b) = (temp_path(&path), temp_path(&path));
+        assert_ne!(a, b);
+        assert_eq!(a.parent(), path.parent());
+        assert!(a.to_string_lossy().ends_with(".tmp"));
+    }
+}
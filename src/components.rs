@@ -1,6 +1,19 @@
-use crate::assets::*;
-use bevy::{ecs::query::QueryData, prelude::*};
+use crate::{
+    assets::*, bake::SkinMask, loaders::HumanDescriptor, measure::HumanMeasurements,
+    skeleton::Skeleton,
+};
+#[cfg(feature = "avian3d")]
+use avian3d::prelude::Collider;
+use bevy::{
+    ecs::query::QueryData, mesh::skinning::SkinnedMesh, platform::collections::HashMap, prelude::*,
+};
 use bevy_inspector_egui::{inspector_options::std_options::NumberDisplay, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+use strum::IntoEnumIterator;
 
 #[derive(QueryData)]
 pub struct HumanQuery {
@@ -16,13 +29,16 @@ pub struct HumanQuery {
     pub tongue: &'static Tongue,
     pub hair: Option<&'static Hair>,
     pub morphs: &'static Morphs,
+    pub macro_sliders: Option<&'static MacroSliders>,
+    pub skin_influences: Option<&'static SkinInfluences>,
     pub clothing: &'static Outfit,
     pub floor_offset: &'static FloorOffset,
     pub clothing_offset: &'static ClothingOffset,
 }
 
 /// Human
-#[derive(Component, Default)]
+#[derive(Component, Default, Reflect)]
+#[reflect(Component, Default)]
 #[require(
     Rig,
     SkinMesh,
@@ -38,27 +54,215 @@ pub struct HumanQuery {
     FloorOffset,
     Morphs,
     HumanDirty, // will trigger a generate when spawned        
+    HumanGeneration,
+    HumanBuildProgress,
     Transform,
     Visibility,
 )]
 pub struct Human;
 
+/// Builds the human from a [`HumanDescriptor`] asset, reapplied when the asset reloads
+///
+/// Load it from a `.human.ron`, `.human.json` or MakeHuman `.mhm` file
+///
+/// Overwrites the part, morph and offset components, edit the asset instead
+#[derive(Component, Clone, Debug, Deref)]
+#[require(Human)]
+pub struct HumanPreset(pub Handle<HumanDescriptor>);
+
 // marker comonent to track if human needs to be rebuilt
+// only what changed since the last build is redone, see HumanBuildCache
 #[derive(Component, Clone, Reflect, Default)]
+#[reflect(Component, Default)]
 pub struct HumanDirty;
 
+/// Build generation of a human, bumped on every rebuild so stale builds are dropped
+///
+/// Shared with in-flight builds through [`HumanBuildToken`]
+#[derive(Component, Clone, Default, Debug)]
+pub struct HumanGeneration(Arc<AtomicU64>);
+
+impl HumanGeneration {
+    /// Latest requested generation
+    pub fn current(&self) -> u64 {
+        self.0.load(Ordering::Acquire)
+    }
+
+    /// Start a new generation, invalidating all older builds
+    pub fn bump(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::AcqRel) + 1
+    }
+
+    /// Token for a build of `generation`
+    pub fn token(&self, generation: u64) -> HumanBuildToken {
+        HumanBuildToken {
+            generation,
+            current: self.0.clone(),
+        }
+    }
+}
+
+/// Where a human is in the build pipeline, for per character loading bars
+#[derive(Component, Reflect, Clone, Copy, Debug, Default, PartialEq)]
+#[reflect(Component)]
+pub enum HumanBuildProgress {
+    /// Nothing built yet, or waiting on the basemesh
+    #[default]
+    Waiting,
+    /// Loading assets, `loaded` of `total` handles done
+    Loading {
+        loaded: usize,
+        total: usize,
+    },
+    /// Applying morph targets and building the skeleton
+    Morphing,
+    /// Fitting and skinning the skin proxy
+    Skinning,
+    /// Fitting parts (hair, eyes, clothes...) to the body
+    Fitting {
+        fitted: usize,
+        total: usize,
+    },
+    /// Spawning meshes and bones
+    Spawning,
+    Complete,
+    /// See [`crate::HumanFailed`]
+    Failed,
+}
+
+impl HumanBuildProgress {
+    /// Rough overall progress from 0 to 1
+    pub fn fraction(&self) -> f32 {
+        let step = |done: usize, total: usize| match total {
+            0 => 0.0,
+            _ => done as f32 / total as f32,
+        };
+        match *self {
+            HumanBuildProgress::Waiting | HumanBuildProgress::Failed => 0.0,
+            HumanBuildProgress::Loading { loaded, total } => 0.5 * step(loaded, total),
+            HumanBuildProgress::Morphing => 0.5,
+            HumanBuildProgress::Skinning => 0.6,
+            HumanBuildProgress::Fitting { fitted, total } => 0.7 + 0.25 * step(fitted, total),
+            HumanBuildProgress::Spawning => 0.95,
+            HumanBuildProgress::Complete => 1.0,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        matches!(
+            self,
+            HumanBuildProgress::Complete | HumanBuildProgress::Failed
+        )
+    }
+}
+
+/// Carried by an async build, goes stale once a newer build is requested
+#[derive(Clone, Debug)]
+pub struct HumanBuildToken {
+    generation: u64,
+    current: Arc<AtomicU64>,
+}
+
+impl HumanBuildToken {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// A newer build was requested, this one can stop
+    pub fn is_stale(&self) -> bool {
+        self.current.load(Ordering::Acquire) != self.generation
+    }
+}
+
+/// Components the body (morphed mesh, skeleton, skin) was built from
+#[derive(Clone, Debug, PartialEq)]
+pub struct HumanBodySource {
+    pub rig: Rig,
+    pub skin_mesh: SkinMesh,
+    pub skin_material: SkinMaterial,
+    pub morphs: Morphs,
+    pub macro_sliders: Option<MacroSliders>,
+    pub influences: SkinInfluences,
+}
+
+impl HumanBodySource {
+    /// Check if changing to `other` needs the body morphed and refit
+    /// (skin material alone can be swapped in place)
+    pub fn needs_rebuild(&self, other: &Self) -> bool {
+        self.rig != other.rig
+            || self.skin_mesh != other.skin_mesh
+            || self.morphs != other.morphs
+            || self.macro_sliders != other.macro_sliders
+            || self.influences != other.influences
+    }
+}
+
+/// Last applied build of a human, lets rebuilds only redo what changed
+///
+/// Remove it to force a full rebuild on the next [`HumanDirty`]
+#[derive(Component, Clone)]
+pub struct HumanBuildCache {
+    pub body: HumanBodySource,
+    pub clothing_offset: f32,
+    /// Base mesh vertices with all morphs applied
+    pub morphed_vertices: Vec<Vec3>,
+    pub skeleton: Skeleton,
+    /// Shared by the skin and all parts
+    pub skinned_mesh: SkinnedMesh,
+    /// Height (max_y - min_y of morphed vertices)
+    pub height: f32,
+    /// Min Y of morphed vertices (for ground offset)
+    pub min_y: f32,
+    /// Horizontal torso radius of the skin
+    pub radius: f32,
+    pub measurements: HumanMeasurements,
+    /// All skin triangles, re-masked when clothing changes
+    pub skin_mask: SkinMask,
+    /// Sorted base mesh vertices currently hidden under clothing
+    pub deleted_vertices: Vec<u32>,
+    #[cfg(feature = "arkit")]
+    /// ARKit morph deltas of the skin, kept for export
+    pub arkit_morphs: Vec<Vec<Vec3>>,
+    /// Spawned part entities and the files they were built from
+    pub parts: Vec<(MHPartSource, Entity)>,
+}
+
 // === PARTS ===
 // Eyes, Eyebrows, Eyelashes, Teeth, Tongue, Hair are generated in build.rs with Component derive
 // They implement MHPart trait for generic handling
 
 // Clothing is the only multi-item part, needs wrapper
 #[derive(Component, Clone, Default, Debug, Reflect, Deref, DerefMut)]
+#[reflect(Component, Default)]
 pub struct Outfit(pub Vec<Clothing>);
 
-/// Uses normals to offset clothing away from skin, hack to reduce z-fighting
-// TODO: replace with Delete Vertex Groups
+/// Physics set up for a human, missing acts as [`HumanPhysics::Capsule`]
+///
+/// Applied on every rebuild and whenever this or [`FloorOffset`] changes
+#[cfg(feature = "avian3d")]
+#[derive(Component, Clone, Debug, Default)]
+pub enum HumanPhysics {
+    /// No collider, removes the body this crate added, your own rigid body is left alone
+    None,
+    /// Dynamic, rotation locked body with a capsule sized to height and torso width
+    #[default]
+    Capsule,
+    /// Dynamic, rotation locked body with your collider, centered at half height
+    Custom(Collider),
+}
+
+/// Marks a rigid body and locked axes inserted by [`HumanPhysics`], so they can be removed again
+#[cfg(feature = "avian3d")]
+#[derive(Component, Clone, Copy, Default, Debug, Reflect)]
+#[reflect(Component, Default)]
+pub struct HumanRigidBody;
+
+/// Uses normals to offset clothing away from skin
+///
+/// Skin under clothing is already hidden by the clothing's mhclo `delete_verts`,
+/// this only helps items that don't list any
 #[derive(Component, Clone, Copy, Default, Debug, Reflect, InspectorOptions, Deref, DerefMut)]
-#[reflect(Component, InspectorOptions)]
+#[reflect(Component, Default, InspectorOptions)]
 pub struct ClothingOffset(
     #[inspector(min = 0.0, max = 0.01, speed = 0.0001, display = NumberDisplay::Slider)] pub f32,
 );
@@ -71,7 +275,7 @@ impl From<f32> for ClothingOffset {
 
 /// Vertical offset to adjust for floor contact (shoes, bare feet, etc)
 #[derive(Component, Clone, Copy, Default, Debug, Reflect, InspectorOptions, Deref, DerefMut)]
-#[reflect(Component, InspectorOptions)]
+#[reflect(Component, Default, InspectorOptions)]
 pub struct FloorOffset(
     #[inspector(min = -0.1, max = 0.1, speed = 0.001, display = NumberDisplay::Slider)] pub f32,
 );
@@ -82,11 +286,253 @@ impl From<f32> for FloorOffset {
     }
 }
 
-#[derive(Component, Clone, Debug, Default, Deref, DerefMut, Reflect)]
+/// How skin weights are cut down to the joints each vertex skins with, missing uses the default
+///
+/// Bevy skins with at most [`SkinInfluences::MAX_JOINTS`] joints per vertex,
+/// `max_influences` outside 1 to 4 is clamped with a warning, see [`SkinInfluences::validated`]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect, InspectorOptions)]
+#[reflect(Component, Default, InspectorOptions)]
+pub struct SkinInfluences {
+    /// Joints per vertex
+    #[inspector(min = 1, max = 4)]
+    pub max_influences: usize,
+    /// Weights below this are dropped before pruning
+    #[inspector(min = 0.0, max = 0.5, speed = 0.001)]
+    pub min_weight: f32,
+    pub pruning: WeightPruning,
+}
+
+impl Default for SkinInfluences {
+    fn default() -> Self {
+        Self {
+            max_influences: 4,
+            min_weight: 0.0,
+            pruning: WeightPruning::default(),
+        }
+    }
+}
+
+impl SkinInfluences {
+    /// Joints per vertex Bevy skins with
+    pub const MAX_JOINTS: usize = 4;
+
+    /// `max_influences` clamped to 1 to [`Self::MAX_JOINTS`], warns if it was outside
+    pub fn validated(self) -> Self {
+        let max_influences = self.max_influences.clamp(1, Self::MAX_JOINTS);
+        if max_influences != self.max_influences {
+            warn!(
+                "SkinInfluences max_influences {} outside 1..={}, using {}",
+                self.max_influences,
+                Self::MAX_JOINTS,
+                max_influences
+            );
+        }
+        Self {
+            max_influences,
+            ..self
+        }
+    }
+}
+
+/// What happens to the weight of joints past [`SkinInfluences::max_influences`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum WeightPruning {
+    /// Drop them and scale the kept joints back up to 1
+    #[default]
+    Renormalize,
+    /// Move each dropped joint's weight to the kept joint fewest bones away from it in the hierarchy,
+    /// the heavier one on ties, then scale the kept joints back up to 1
+    Redistribute,
+}
+
+#[derive(Component, Clone, Debug, Default, PartialEq, Deref, DerefMut, Reflect)]
+#[reflect(Component, Default)]
 pub struct Morphs(pub Vec<Morph>);
 
+/// MakeHuman's continuous macro sliders, all 0 to 1 with 0.5 as average
+///
+/// Weighted across the combined macrodetail, height, proportions and breast targets each build,
+/// on top of [`Morphs`]. Don't also add ethnic [`MacroMorph`]s to [`Morphs`], they would count twice.
+#[derive(
+    Component, Clone, Copy, Debug, PartialEq, Reflect, InspectorOptions, Serialize, Deserialize,
+)]
+#[reflect(Component, Default, InspectorOptions, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MacroSliders {
+    /// 0 female, 1 male
+    #[inspector(min = 0.0, max = 1.0, speed = 0.01, display = NumberDisplay::Slider)]
+    pub gender: f32,
+    /// 0 is 1 year, 0.1875 is 11, 0.5 is 25 and 1 is 90, see [`age_slider`]
+    #[inspector(min = 0.0, max = 1.0, speed = 0.01, display = NumberDisplay::Slider)]
+    pub age: f32,
+    #[inspector(min = 0.0, max = 1.0, speed = 0.01, display = NumberDisplay::Slider)]
+    pub muscle: f32,
+    #[inspector(min = 0.0, max = 1.0, speed = 0.01, display = NumberDisplay::Slider)]
+    pub weight: f32,
+    #[inspector(min = 0.0, max = 1.0, speed = 0.01, display = NumberDisplay::Slider)]
+    pub height: f32,
+    /// 0 uncommon, 1 ideal
+    #[inspector(min = 0.0, max = 1.0, speed = 0.01, display = NumberDisplay::Slider)]
+    pub proportions: f32,
+    /// Ethnic mix, normalized when weighting
+    #[inspector(min = 0.0, max = 1.0, speed = 0.01, display = NumberDisplay::Slider)]
+    pub african: f32,
+    #[inspector(min = 0.0, max = 1.0, speed = 0.01, display = NumberDisplay::Slider)]
+    pub asian: f32,
+    #[inspector(min = 0.0, max = 1.0, speed = 0.01, display = NumberDisplay::Slider)]
+    pub caucasian: f32,
+    /// Cup size, female only
+    #[inspector(min = 0.0, max = 1.0, speed = 0.01, display = NumberDisplay::Slider)]
+    pub breast_size: f32,
+    /// Female only
+    #[inspector(min = 0.0, max = 1.0, speed = 0.01, display = NumberDisplay::Slider)]
+    pub breast_firmness: f32,
+}
+
+impl Default for MacroSliders {
+    fn default() -> Self {
+        Self {
+            gender: 0.5,
+            age: 0.5,
+            muscle: 0.5,
+            weight: 0.5,
+            height: 0.5,
+            proportions: 0.5,
+            african: 1.0 / 3.0,
+            asian: 1.0 / 3.0,
+            caucasian: 1.0 / 3.0,
+            breast_size: 0.5,
+            breast_firmness: 0.5,
+        }
+    }
+}
+
+impl MacroSliders {
+    /// Defaults with gender, age and ethnicity read back from ethnic macro morphs, see [`ethnic_macro_sliders`]
+    pub fn from_ethnic_morphs(morphs: &[Morph]) -> Self {
+        let mut sliders = Self::default();
+        if let Some((gender, age, ethnic)) = ethnic_macro_sliders(morphs) {
+            sliders.gender = gender;
+            sliders.age = age;
+            sliders.set_ethnic(ethnic);
+        }
+        sliders
+    }
+
+    /// Ethnic mix in [`ETHNICITIES`] order
+    pub fn ethnic(&self) -> [f32; 3] {
+        [self.african, self.asian, self.caucasian]
+    }
+
+    pub fn set_ethnic(&mut self, ethnic: [f32; 3]) {
+        [self.african, self.asian, self.caucasian] = ethnic;
+    }
+
+    /// Weight of every macro target file these sliders affect
+    ///
+    /// A target like `universal-female-young-averagemuscle-maxweight` gets the product
+    /// of its parts' weights, files with parts not driven by a slider are skipped
+    pub fn target_weights(&self) -> Vec<(&'static str, f32)> {
+        let mut parts: HashMap<String, f32> = HashMap::default();
+        parts.insert("universal".into(), 1.0);
+        let gender = self.gender.clamp(0.0, 1.0);
+        parts.insert("female".into(), 1.0 - gender);
+        parts.insert("male".into(), gender);
+        for (age, w) in AGES.iter().zip(age_weights(self.age)) {
+            parts.insert(age.to_string(), w);
+        }
+        let ethnic = self.ethnic().map(|e| e.max(0.0));
+        let sum: f32 = ethnic.iter().sum();
+        for (name, e) in ETHNICITIES.iter().zip(ethnic) {
+            let w = if sum > 0.0 { e / sum } else { 1.0 / 3.0 };
+            parts.insert(name.to_lowercase(), w);
+        }
+        for (name, value) in [
+            ("muscle", self.muscle),
+            ("weight", self.weight),
+            ("cup", self.breast_size),
+            ("firmness", self.breast_firmness),
+        ] {
+            let [min, average, max] = three_way(value);
+            parts.insert(format!("min{name}"), min);
+            parts.insert(format!("average{name}"), average);
+            parts.insert(format!("max{name}"), max);
+        }
+        // no average target, the base mesh is average
+        let [min, max] = two_way(self.height);
+        parts.insert("minheight".into(), min);
+        parts.insert("maxheight".into(), max);
+        let [min, max] = two_way(self.proportions);
+        parts.insert("uncommonproportions".into(), min);
+        parts.insert("idealproportions".into(), max);
+
+        MacroMorph::iter()
+            .flat_map(|m| {
+                let (min, avg, max) = m.paths();
+                [min, avg, max]
+            })
+            .flatten()
+            .filter_map(|path| {
+                let stem = std::path::Path::new(path).file_stem()?.to_str()?;
+                let weight = stem
+                    .split('-')
+                    .map(|part| parts.get(part).copied())
+                    .product::<Option<f32>>()?;
+                (weight > 0.001).then_some((path, weight))
+            })
+            .collect()
+    }
+}
+
+/// Target files and weights a build applies for `morphs` and the `sliders`' macro targets
+pub fn morph_target_weights(
+    morphs: &[Morph],
+    sliders: Option<&MacroSliders>,
+) -> Vec<(&'static str, f32)> {
+    let mut weights = Vec::new();
+    for Morph { target, value } in morphs {
+        if target.is_interpolated() {
+            // Interpolated macro morph: value 0..1 maps to min->avg->max
+            if let Some((min_path, avg_path, max_path)) = target.macro_paths() {
+                let v = value.clamp(0.0, 1.0);
+                if v < 0.5 {
+                    // Blend min -> avg
+                    let t = v / 0.5;
+                    weights.extend(min_path.map(|p| (p, 1.0 - t)));
+                    weights.extend(avg_path.map(|p| (p, t)));
+                } else {
+                    // Blend avg -> max
+                    let t = (v - 0.5) / 0.5;
+                    weights.extend(avg_path.map(|p| (p, 1.0 - t)));
+                    weights.extend(max_path.map(|p| (p, t)));
+                }
+            }
+        } else if let Some(path) = target.target_path(*value) {
+            // Simple morph - single path
+            weights.push((path, value.abs()));
+        }
+    }
+    if let Some(sliders) = sliders {
+        weights.extend(sliders.target_weights());
+    }
+    weights
+}
+
+/// Min, average and max weights of a 0..1 slider with 0.5 as average
+fn three_way(value: f32) -> [f32; 3] {
+    let [min, max] = two_way(value);
+    [min, 1.0 - min - max, max]
+}
+
+/// Min and max weights of a 0..1 slider with 0.5 as neutral
+fn two_way(value: f32) -> [f32; 2] {
+    let value = value.clamp(0.0, 1.0);
+    [(1.0 - 2.0 * value).max(0.0), (2.0 * value - 1.0).max(0.0)]
+}
+
 // Marker components body parts
-#[derive(Component, Copy, Clone, strum::Display, PartialEq, Eq, Debug, Reflect)]
+#[derive(Component, Copy, Clone, strum::Display, PartialEq, Eq, Hash, Debug, Reflect)]
+#[reflect(Component)]
 pub enum MHTag {
     Armature,
     Skin,
@@ -148,3 +594,198 @@ pub struct JawMesh;
 
 #[derive(Component)]
 pub struct UpperJawMesh;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AGE_BREAKPOINTS: [f32; 4] = [0.0, 0.1875, 0.5, 1.0];
+
+    fn stem(path: &str) -> &str {
+        std::path::Path::new(path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(path)
+    }
+
+    /// Summed weight of the macro files whose name parts pass `filter`
+    fn share(weights: &[(&'static str, f32)], filter: impl Fn(&[&str]) -> bool) -> f32 {
+        weights
+            .iter()
+            .filter(|(p, _)| filter(&stem(p).split('-').collect::<Vec<_>>()))
+            .map(|(_, w)| w)
+            .sum()
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_age_weights_breakpoints() {
+        for (i, age) in AGE_BREAKPOINTS.into_iter().enumerate() {
+            let mut expected = [0.0; 4];
+            expected[i] = 1.0;
+            assert_eq!(age_weights(age), expected, "age {age}");
+        }
+        // halfway between young and old
+        assert_eq!(age_weights(0.75), [0.0, 0.0, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_target_weights_table() {
+        for gender in [0.0, 0.5, 1.0] {
+            for (i, age) in AGE_BREAKPOINTS.into_iter().enumerate() {
+                let weights = MacroSliders {
+                    gender,
+                    age,
+                    ..default()
+                }
+                .target_weights();
+                let ethnic = |parts: &[&str]| {
+                    parts.len() == 3 && ETHNICITIES.iter().any(|e| e.eq_ignore_ascii_case(parts[0]))
+                };
+                let universal = |parts: &[&str]| parts.len() == 5 && parts[0] == "universal";
+
+                let case = format!("gender {gender} age {age}");
+                assert!(close(share(&weights, ethnic), 1.0), "{case}");
+                assert!(close(share(&weights, universal), 1.0), "{case}");
+                assert!(
+                    close(share(&weights, |p| ethnic(p) && p[1] == "male"), gender),
+                    "{case}"
+                );
+                assert!(
+                    close(share(&weights, |p| ethnic(p) && p[2] == AGES[i]), 1.0),
+                    "{case}"
+                );
+                // average height has no targets
+                assert_eq!(
+                    share(&weights, |p| p.iter().any(|p| p.ends_with("height"))),
+                    0.0
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_morph_target_weights_table() {
+        let sliders = MacroSliders::default();
+        assert_eq!(
+            morph_target_weights(&[], Some(&sliders)),
+            sliders.target_weights()
+        );
+
+        let target = MorphTarget::iter()
+            .find(|t| {
+                t.is_interpolated() && matches!(t.macro_paths(), Some((Some(_), Some(_), Some(_))))
+            })
+            .unwrap();
+        let (min, avg, max) = target.macro_paths().unwrap();
+        for (value, expected) in [
+            (0.0, [1.0, 0.0, 0.0]),
+            (0.25, [0.5, 0.5, 0.0]),
+            (0.5, [0.0, 1.0, 0.0]),
+            (1.0, [0.0, 0.0, 1.0]),
+        ] {
+            let weights = morph_target_weights(&[Morph::new(target, value)], None);
+            let weight = |path| {
+                weights
+                    .iter()
+                    .filter(|(p, _)| Some(*p) == path)
+                    .map(|(_, w)| w)
+                    .sum::<f32>()
+            };
+            assert_eq!([weight(min), weight(avg), weight(max)], expected, "{value}");
+            assert!(close(weights.iter().map(|(_, w)| w).sum(), 1.0));
+        }
+    }
+
+    #[test]
+    fn test_progress_fraction() {
+        use HumanBuildProgress::*;
+        let loading = |loaded, total| Loading { loaded, total };
+        let fitting = |fitted, total| Fitting { fitted, total };
+        let stages = [
+            Waiting,
+            loading(0, 4),
+            loading(3, 4),
+            Morphing,
+            Skinning,
+            fitting(0, 2),
+            fitting(2, 2),
+            Spawning,
+            Complete,
+        ];
+        let fractions: Vec<f32> = stages.iter().map(HumanBuildProgress::fraction).collect();
+        assert!(fractions.windows(2).all(|w| w[0] <= w[1]), "{fractions:?}");
+        assert_eq!((fractions[0], fractions[8]), (0.0, 1.0));
+        // nothing to load or fit is no division by zero
+        assert_eq!(loading(0, 0).fraction(), 0.0);
+        assert_eq!(fitting(0, 0).fraction(), 0.7);
+
+        assert!(Complete.is_done() && Failed.is_done());
+        assert!(stages[..8].iter().all(|p| !p.is_done()));
+    }
+
+    #[test]
+    fn test_build_token_goes_stale() {
+        let generation = HumanGeneration::default();
+        let first = generation.token(generation.bump());
+        assert!(!first.is_stale());
+
+        // clones share the counter, like the copy moved into the build task
+        let second = generation.clone().token(generation.bump());
+        assert_eq!(second.generation(), first.generation() + 1);
+        assert!(first.is_stale());
+        assert!(!second.is_stale());
+        assert_eq!(generation.current(), second.generation());
+    }
+
+    #[test]
+    fn test_needs_rebuild() {
+        let source = HumanBodySource {
+            rig: Rig::default(),
+            skin_mesh: SkinMesh::default(),
+            skin_material: SkinMaterial::default(),
+            morphs: Morphs::default(),
+            macro_sliders: Some(MacroSliders::default()),
+            influences: SkinInfluences::default(),
+        };
+        assert!(!source.needs_rebuild(&source.clone()));
+
+        // skin material is swapped in place
+        let other_material = SkinMaterial::iter()
+            .find(|m| *m != source.skin_material)
+            .unwrap();
+        let reskinned = HumanBodySource {
+            skin_material: other_material,
+            ..source.clone()
+        };
+        assert!(!source.needs_rebuild(&reskinned));
+
+        let target = MorphTarget::iter().next().unwrap();
+        let morphed = HumanBodySource {
+            morphs: Morphs(vec![Morph::new(target, 0.5)]),
+            ..source.clone()
+        };
+        assert!(source.needs_rebuild(&morphed));
+
+        let older = HumanBodySource {
+            macro_sliders: Some(MacroSliders {
+                age: 1.0,
+                ..default()
+            }),
+            ..source.clone()
+        };
+        assert!(source.needs_rebuild(&older));
+
+        let influences = HumanBodySource {
+            influences: SkinInfluences {
+                max_influences: 2,
+                ..default()
+            },
+            ..source.clone()
+        };
+        assert!(source.needs_rebuild(&influences));
+    }
+}
@@ -0,0 +1,589 @@
+//! Export a built human to binary glTF (.glb)
+//!
+//! Writes the skin and all parts with their materials, the joint hierarchy with
+//! inverse bind matrices, and ARKit morph targets on the skin
+
+use crate::components::*;
+use bevy::{
+    mesh::{Indices, VertexAttributeValues},
+    platform::collections::HashMap,
+    prelude::*,
+};
+#[cfg(feature = "arkit")]
+use bevy_blend_shapes::ARKit;
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "arkit")]
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+#[derive(Debug, Error)]
+pub enum GlbExportError {
+    #[error("Human has not finished building, wait for HumanComplete")]
+    NotBuilt,
+    #[error("Mesh for {0} is not loaded")]
+    MissingMesh(String),
+    #[error("Mesh for {0} has no {1} attribute")]
+    MissingAttribute(String, &'static str),
+    #[error("Failed to write glb: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to encode glTF json: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Command writing a built human to a .glb file
+///
+/// Textures are embedded from their source files under `asset_dir`
+pub struct ExportGlb {
+    pub entity: Entity,
+    pub path: PathBuf,
+    pub asset_dir: PathBuf,
+}
+
+impl ExportGlb {
+    pub fn new(entity: Entity, path: impl Into<PathBuf>) -> Self {
+        Self {
+            entity,
+            path: path.into(),
+            asset_dir: PathBuf::from("assets"),
+        }
+    }
+}
+
+impl Command<Result<(), GlbExportError>> for ExportGlb {
+    fn apply(self, world: &mut World) -> Result<(), GlbExportError> {
+        let glb = export_glb(world, self.entity, &self.asset_dir)?;
+        std::fs::write(&self.path, glb)?;
+        info!("Exported {} to {:?}", self.entity, self.path);
+        Ok(())
+    }
+}
+
+/// Encode a built human as a binary glTF
+pub fn export_glb(
+    world: &World,
+    entity: Entity,
+    asset_dir: &Path,
+) -> Result<Vec<u8>, GlbExportError> {
+    let cache = world
+        .get::<HumanBuildCache>(entity)
+        .ok_or(GlbExportError::NotBuilt)?;
+    let skeleton = &cache.skeleton;
+    let name = world
+        .get::<Name>(entity)
+        .map(|n| n.to_string())
+        .unwrap_or_else(|| "Human".to_string());
+
+    let mut gltf = GltfBuilder::new(world, asset_dir);
+
+    // node 0 is the human, bones follow in skeleton order
+    let bone_node = |i: usize| i + 1;
+    let mut root_children = vec![];
+    for (i, bone) in skeleton.bones.iter().enumerate() {
+        let t = skeleton.bind_pose[i];
+        let children: Vec<usize> = skeleton
+            .hierarchy
+            .iter()
+            .enumerate()
+            .filter(|(_, parent)| **parent == Some(i))
+            .map(|(c, _)| bone_node(c))
+            .collect();
+        let mut node = json!({
+            "name": bone.name,
+            "translation": t.translation.to_array(),
+            "rotation": t.rotation.to_array(),
+            "scale": t.scale.to_array(),
+        });
+        if !children.is_empty() {
+            node["children"] = json!(children);
+        }
+        gltf.nodes.push(node);
+        if skeleton.hierarchy[i].is_none() {
+            root_children.push(bone_node(i));
+        }
+    }
+
+    let inverse_bind_matrices: Vec<f32> = skeleton
+        .inverse_bind_matrices
+        .iter()
+        .flat_map(|m| m.to_cols_array())
+        .collect();
+    let ibm = gltf.accessor(
+        &f32_bytes(&inverse_bind_matrices),
+        FLOAT,
+        skeleton.bones.len(),
+        "MAT4",
+        None,
+    );
+    gltf.skins.push(json!({
+        "inverseBindMatrices": ibm,
+        "joints": (0..skeleton.bones.len()).map(bone_node).collect::<Vec<_>>(),
+    }));
+
+    // skin on the human entity, then parts
+    #[cfg(feature = "arkit")]
+    let morphs = Some(cache.arkit_morphs.as_slice()).filter(|m| !m.is_empty());
+    #[cfg(not(feature = "arkit"))]
+    let morphs: Option<&[Vec<Vec3>]> = None;
+    let mut mesh_entities = vec![(entity, "Skin".to_string(), morphs)];
+    for (source, part) in &cache.parts {
+        let part_name = Path::new(source.mhclo)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| source.tag.to_string());
+        mesh_entities.push((*part, part_name, None));
+    }
+
+    for (e, mesh_name, morphs) in mesh_entities {
+        let Some(mesh3d) = world.get::<Mesh3d>(e) else {
+            continue;
+        };
+        let mesh = world
+            .resource::<Assets<Mesh>>()
+            .get(&mesh3d.0)
+            .ok_or_else(|| GlbExportError::MissingMesh(mesh_name.clone()))?;
+        let material = world
+            .get::<MeshMaterial3d<StandardMaterial>>(e)
+            .and_then(|m| world.resource::<Assets<StandardMaterial>>().get(&m.0))
+            .map(|m| gltf.material(&mesh_name, m));
+        let mesh_index = gltf.mesh(&mesh_name, mesh, material, morphs)?;
+        let mut node = json!({ "name": mesh_name, "mesh": mesh_index });
+        if gltf.is_skinned(mesh_index) {
+            node["skin"] = json!(0);
+        }
+        gltf.nodes.push(node);
+        root_children.push(gltf.nodes.len() - 1);
+    }
+
+    gltf.nodes[0] = json!({ "name": name, "children": root_children });
+    gltf.finish()
+}
+
+/// Accumulates glTF json arrays and the binary buffer
+struct GltfBuilder<'a> {
+    world: &'a World,
+    asset_dir: &'a Path,
+    bin: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    nodes: Vec<Value>,
+    meshes: Vec<Value>,
+    skins: Vec<Value>,
+    materials: Vec<Value>,
+    textures: Vec<Value>,
+    images: Vec<Value>,
+    /// Image asset -> texture index, None if it couldn't be embedded
+    texture_lookup: HashMap<AssetId<Image>, Option<usize>>,
+}
+
+impl<'a> GltfBuilder<'a> {
+    fn new(world: &'a World, asset_dir: &'a Path) -> Self {
+        Self {
+            world,
+            asset_dir,
+            bin: vec![],
+            buffer_views: vec![],
+            accessors: vec![],
+            // placeholder root, filled in once children are known
+            nodes: vec![Value::Null],
+            meshes: vec![],
+            skins: vec![],
+            materials: vec![],
+            textures: vec![],
+            images: vec![],
+            texture_lookup: HashMap::default(),
+        }
+    }
+
+    fn buffer_view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        while !self.bin.len().is_multiple_of(4) {
+            self.bin.push(0);
+        }
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": self.bin.len(),
+            "byteLength": bytes.len(),
+        });
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+        self.bin.extend_from_slice(bytes);
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
+
+    fn accessor(
+        &mut self,
+        bytes: &[u8],
+        component_type: u32,
+        count: usize,
+        kind: &str,
+        target: Option<u32>,
+    ) -> usize {
+        let view = self.buffer_view(bytes, target);
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": component_type,
+            "count": count,
+            "type": kind,
+        }));
+        self.accessors.len() - 1
+    }
+
+    /// VEC3 float accessor with min/max, required for positions
+    fn vec3_accessor(&mut self, data: &[[f32; 3]]) -> usize {
+        let (min, max) = data.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), p| {
+                let p = Vec3::from_array(*p);
+                (min.min(p), max.max(p))
+            },
+        );
+        let flat: Vec<f32> = data.iter().flatten().copied().collect();
+        let index = self.accessor(
+            &f32_bytes(&flat),
+            FLOAT,
+            data.len(),
+            "VEC3",
+            Some(ARRAY_BUFFER),
+        );
+        if !data.is_empty() {
+            self.accessors[index]["min"] = json!(min.to_array());
+            self.accessors[index]["max"] = json!(max.to_array());
+        }
+        index
+    }
+
+    fn mesh(
+        &mut self,
+        name: &str,
+        mesh: &Mesh,
+        material: Option<usize>,
+        morphs: Option<&[Vec<Vec3>]>,
+    ) -> Result<usize, GlbExportError> {
+        let missing = |attr| GlbExportError::MissingAttribute(name.to_string(), attr);
+        let positions = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .and_then(|a| a.as_float3())
+            .ok_or_else(|| missing("position"))?;
+
+        let mut attributes = json!({ "POSITION": self.vec3_accessor(positions) });
+        if let Some(normals) = mesh
+            .attribute(Mesh::ATTRIBUTE_NORMAL)
+            .and_then(|a| a.as_float3())
+        {
+            let flat: Vec<f32> = normals.iter().flatten().copied().collect();
+            attributes["NORMAL"] = json!(self.accessor(
+                &f32_bytes(&flat),
+                FLOAT,
+                normals.len(),
+                "VEC3",
+                Some(ARRAY_BUFFER)
+            ));
+        }
+        if let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            let flat: Vec<f32> = uvs.iter().flatten().copied().collect();
+            attributes["TEXCOORD_0"] = json!(self.accessor(
+                &f32_bytes(&flat),
+                FLOAT,
+                uvs.len(),
+                "VEC2",
+                Some(ARRAY_BUFFER)
+            ));
+        }
+        if let Some(VertexAttributeValues::Uint16x4(joints)) =
+            mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX)
+        {
+            let bytes: Vec<u8> = joints
+                .iter()
+                .flatten()
+                .flat_map(|j| j.to_le_bytes())
+                .collect();
+            attributes["JOINTS_0"] = json!(self.accessor(
+                &bytes,
+                UNSIGNED_SHORT,
+                joints.len(),
+                "VEC4",
+                Some(ARRAY_BUFFER)
+            ));
+        }
+        if let Some(VertexAttributeValues::Float32x4(weights)) =
+            mesh.attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT)
+        {
+            let flat: Vec<f32> = weights.iter().flatten().copied().collect();
+            attributes["WEIGHTS_0"] = json!(self.accessor(
+                &f32_bytes(&flat),
+                FLOAT,
+                weights.len(),
+                "VEC4",
+                Some(ARRAY_BUFFER)
+            ));
+        }
+
+        let mut primitive = json!({ "attributes": attributes, "mode": 4 });
+        if let Some(indices) = mesh.indices() {
+            let bytes: Vec<u8> = indices
+                .iter()
+                .flat_map(|i| (i as u32).to_le_bytes())
+                .collect();
+            primitive["indices"] = json!(self.accessor(
+                &bytes,
+                UNSIGNED_INT,
+                indices.len(),
+                "SCALAR",
+                Some(ELEMENT_ARRAY_BUFFER)
+            ));
+        }
+        if let Some(material) = material {
+            primitive["material"] = json!(material);
+        }
+
+        let mut gltf_mesh = json!({ "name": name });
+        if let Some(morphs) = morphs {
+            let targets: Vec<Value> = morphs
+                .iter()
+                .map(|offsets| {
+                    let data: Vec<[f32; 3]> = offsets.iter().map(|o| o.to_array()).collect();
+                    json!({ "POSITION": self.vec3_accessor(&data) })
+                })
+                .collect();
+            primitive["targets"] = json!(targets);
+            gltf_mesh["weights"] = json!(vec![0.0; morphs.len()]);
+            #[cfg(feature = "arkit")]
+            {
+                let names: Vec<String> = ARKit::iter().map(|s| s.to_string()).collect();
+                gltf_mesh["extras"] = json!({ "targetNames": names });
+            }
+        }
+        gltf_mesh["primitives"] = json!([primitive]);
+
+        self.meshes.push(gltf_mesh);
+        Ok(self.meshes.len() - 1)
+    }
+
+    /// Mesh has joints and weights, glTF only allows a skin on those
+    fn is_skinned(&self, mesh: usize) -> bool {
+        let attributes = &self.meshes[mesh]["primitives"][0]["attributes"];
+        attributes.get("JOINTS_0").is_some() && attributes.get("WEIGHTS_0").is_some()
+    }
+
+    fn material(&mut self, name: &str, mat: &StandardMaterial) -> usize {
+        let base = mat.base_color.to_linear();
+        let emissive = mat.emissive;
+        let mut pbr = json!({
+            "baseColorFactor": [base.red, base.green, base.blue, base.alpha],
+            "metallicFactor": mat.metallic,
+            "roughnessFactor": mat.perceptual_roughness,
+        });
+        let mut material = json!({
+            "name": name,
+            "emissiveFactor": [
+                emissive.red.clamp(0.0, 1.0),
+                emissive.green.clamp(0.0, 1.0),
+                emissive.blue.clamp(0.0, 1.0),
+            ],
+            "doubleSided": mat.double_sided || mat.cull_mode.is_none(),
+        });
+        match mat.alpha_mode {
+            AlphaMode::Opaque => material["alphaMode"] = json!("OPAQUE"),
+            AlphaMode::Mask(cutoff) => {
+                material["alphaMode"] = json!("MASK");
+                material["alphaCutoff"] = json!(cutoff);
+            }
+            _ => material["alphaMode"] = json!("BLEND"),
+        }
+
+        if let Some(t) = self.texture(mat.base_color_texture.as_ref()) {
+            pbr["baseColorTexture"] = json!({ "index": t });
+        }
+        if let Some(t) = self.texture(mat.metallic_roughness_texture.as_ref()) {
+            pbr["metallicRoughnessTexture"] = json!({ "index": t });
+        }
+        if let Some(t) = self.texture(mat.normal_map_texture.as_ref()) {
+            material["normalTexture"] = json!({ "index": t });
+        }
+        if let Some(t) = self.texture(mat.occlusion_texture.as_ref()) {
+            material["occlusionTexture"] = json!({ "index": t });
+        }
+        if let Some(t) = self.texture(mat.emissive_texture.as_ref()) {
+            material["emissiveTexture"] = json!({ "index": t });
+        }
+        material["pbrMetallicRoughness"] = pbr;
+
+        self.materials.push(material);
+        self.materials.len() - 1
+    }
+
+    /// Embed the image's source file, only png and jpeg are valid in glTF
+    fn texture(&mut self, handle: Option<&Handle<Image>>) -> Option<usize> {
+        let handle = handle?;
+        if let Some(index) = self.texture_lookup.get(&handle.id()) {
+            return *index;
+        }
+
+        let index = (|| {
+            let path = self
+                .world
+                .resource::<AssetServer>()
+                .get_path(handle.id())?
+                .path()
+                .to_path_buf();
+            let mime = match path.extension()?.to_str()?.to_lowercase().as_str() {
+                "png" => "image/png",
+                "jpg" | "jpeg" => "image/jpeg",
+                _ => {
+                    warn!(
+                        "Skipping texture {:?}, glTF only supports png and jpeg",
+                        path
+                    );
+                    return None;
+                }
+            };
+            let bytes = match std::fs::read(self.asset_dir.join(&path)) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    warn!("Skipping texture {:?}: {}", path, e);
+                    return None;
+                }
+            };
+
+            let view = self.buffer_view(&bytes, None);
+            self.images.push(json!({
+                "name": path.file_stem().map(|s| s.to_string_lossy()),
+                "bufferView": view,
+                "mimeType": mime,
+            }));
+            self.textures.push(json!({
+                "source": self.images.len() - 1,
+                "sampler": 0,
+            }));
+            Some(self.textures.len() - 1)
+        })();
+
+        self.texture_lookup.insert(handle.id(), index);
+        index
+    }
+
+    /// Assemble the json and binary chunks into a glb
+    fn finish(mut self) -> Result<Vec<u8>, GlbExportError> {
+        while !self.bin.len().is_multiple_of(4) {
+            self.bin.push(0);
+        }
+
+        let mut root = json!({
+            "asset": { "version": "2.0", "generator": "bevy_make_human" },
+            "scene": 0,
+            "scenes": [{ "nodes": [0] }],
+            "nodes": self.nodes,
+            "meshes": self.meshes,
+            "skins": self.skins,
+            "accessors": self.accessors,
+            "bufferViews": self.buffer_views,
+            "buffers": [{ "byteLength": self.bin.len() }],
+        });
+        if !self.materials.is_empty() {
+            root["materials"] = json!(self.materials);
+        }
+        if !self.textures.is_empty() {
+            root["textures"] = json!(self.textures);
+            root["images"] = json!(self.images);
+            root["samplers"] = json!([{
+                "magFilter": 9729, // LINEAR
+                "minFilter": 9987, // LINEAR_MIPMAP_LINEAR
+            }]);
+        }
+
+        let mut json = serde_json::to_vec(&root)?;
+        while !json.len().is_multiple_of(4) {
+            json.push(b' ');
+        }
+
+        let total = 12 + 8 + json.len() + 8 + self.bin.len();
+        let mut glb = Vec::with_capacity(total);
+        glb.extend_from_slice(b"glTF");
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(total as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"JSON");
+        glb.extend_from_slice(&json);
+        glb.extend_from_slice(&(self.bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"BIN\0");
+        glb.extend_from_slice(&self.bin);
+        Ok(glb)
+    }
+}
+
+fn f32_bytes(data: &[f32]) -> Vec<u8> {
+    data.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{asset::RenderAssetUsages, mesh::PrimitiveTopology};
+
+    fn triangle() -> Mesh {
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+        )
+        .with_inserted_indices(Indices::U32(vec![0, 1, 2]))
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> usize {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize
+    }
+
+    #[test]
+    fn test_glb_layout() {
+        let world = World::new();
+        let mut gltf = GltfBuilder::new(&world, Path::new("."));
+        let plain = gltf.mesh("plain", &triangle(), None, None).unwrap();
+        let skinned = triangle()
+            .with_inserted_attribute(
+                Mesh::ATTRIBUTE_JOINT_INDEX,
+                VertexAttributeValues::Uint16x4(vec![[0, 0, 0, 0]; 3]),
+            )
+            .with_inserted_attribute(
+                Mesh::ATTRIBUTE_JOINT_WEIGHT,
+                vec![[1.0f32, 0.0, 0.0, 0.0]; 3],
+            );
+        let skinned = gltf.mesh("skinned", &skinned, None, None).unwrap();
+        assert!(!gltf.is_skinned(plain));
+        assert!(gltf.is_skinned(skinned));
+        gltf.nodes[0] = json!({ "name": "root" });
+
+        let glb = gltf.finish().unwrap();
+        // header: magic, version, total length
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(u32_at(&glb, 4), 2);
+        assert_eq!(u32_at(&glb, 8), glb.len());
+
+        // json chunk, then bin chunk, both 4 byte aligned
+        let json_len = u32_at(&glb, 12);
+        assert_eq!(&glb[16..20], b"JSON");
+        assert_eq!(json_len % 4, 0);
+        let bin_start = 20 + json_len;
+        let bin_len = u32_at(&glb, bin_start);
+        assert_eq!(&glb[bin_start + 4..bin_start + 8], b"BIN\0");
+        assert_eq!(bin_len % 4, 0);
+        assert_eq!(bin_start + 8 + bin_len, glb.len());
+
+        let root: Value = serde_json::from_slice(&glb[20..bin_start]).unwrap();
+        assert_eq!(root["buffers"][0]["byteLength"], json!(bin_len));
+        assert_eq!(root["meshes"].as_array().map(Vec::len), Some(2));
+        for view in root["bufferViews"].as_array().unwrap() {
+            assert_eq!(view["byteOffset"].as_u64().unwrap() % 4, 0);
+        }
+    }
+}
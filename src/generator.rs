@@ -0,0 +1,506 @@
+//! Seeded random humans for crowds and NPCs
+//!
+//! Same seed and settings give the same [`HumanDescriptor`], [`blend_humans`] and [`breed_humans`] mix two of them
+
+use crate::{
+    assets::*,
+    components::MacroSliders,
+    loaders::HumanDescriptor,
+    symmetry::{Side, symmetrize_morphs},
+};
+use rand::{Rng, SeedableRng, seq::IndexedRandom};
+use rand_chacha::ChaCha8Rng;
+use rand_distr::{Distribution, Normal};
+use std::{fmt::Display, ops::RangeInclusive, path::Path};
+use strum::{EnumProperty, IntoEnumIterator};
+
+/// Target folders varied by [`HumanGenerator::body_variation`]
+const BODY_GROUPS: [&str; 19] = [
+    "arms", "buttocks", "cheek", "chin", "ears", "eyebrows", "eyes", "feet", "forehead", "hands",
+    "head", "hip", "legs", "mouth", "neck", "nose", "pelvis", "stomach", "torso",
+];
+
+/// Constraints for random humans, see [`HumanGenerator::generate`]
+#[derive(Clone, Debug)]
+pub struct HumanGenerator {
+    /// Gender slider range, 0 female to 1 male
+    pub gender: RangeInclusive<f32>,
+    /// Age slider range, see [`age_slider`] to convert from years
+    pub age: RangeInclusive<f32>,
+    /// Population mix of African, Asian and Caucasian, need not sum to 1
+    pub ethnicity: [f32; 3],
+    /// Clothing with any of these mhclo tags (lowercase), empty allows all
+    pub clothing_tags: Vec<String>,
+    /// Number of clothing items
+    pub outfit_size: RangeInclusive<usize>,
+    /// Chance of no hair
+    pub bald_chance: f32,
+    /// Standard deviation of body morphs and muscle, weight, height, proportions and breast sliders, 0 disables them
+    pub body_variation: f32,
+    /// Chance each body morph is used
+    pub body_morph_chance: f32,
+}
+
+impl Default for HumanGenerator {
+    fn default() -> Self {
+        Self {
+            gender: 0.0..=1.0,
+            // 18 to 60 years
+            age: age_slider(18.0)..=age_slider(60.0),
+            ethnicity: [1.0; 3],
+            clothing_tags: vec![],
+            outfit_size: 1..=3,
+            bald_chance: 0.1,
+            body_variation: 0.2,
+            body_morph_chance: 0.25,
+        }
+    }
+}
+
+impl HumanGenerator {
+    /// Random human for `seed`
+    pub fn generate(&self, seed: u64) -> HumanDescriptor {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+        let gender = self.sample_gender(&mut rng);
+        let age = sample_range(&mut rng, &self.age);
+        let ethnic = self.sample_ethnicity(&mut rng);
+        let male = gender >= 0.5;
+
+        let mut sliders = MacroSliders {
+            gender,
+            age,
+            ..Default::default()
+        };
+        sliders.set_ethnic(ethnic);
+        let mut morphs = vec![];
+        if self.body_variation > 0.0
+            && let Ok(normal) = Normal::new(0.0, self.body_variation)
+        {
+            for slider in [
+                &mut sliders.muscle,
+                &mut sliders.weight,
+                &mut sliders.height,
+                &mut sliders.proportions,
+                &mut sliders.breast_size,
+                &mut sliders.breast_firmness,
+            ] {
+                *slider = (0.5 + normal.sample(&mut rng)).clamp(0.0, 1.0);
+            }
+            for target in MorphTarget::iter().filter(is_body_morph) {
+                if !rng.random_bool(self.body_morph_chance.clamp(0.0, 1.0) as f64) {
+                    continue;
+                }
+                let value: f32 = normal.sample(&mut rng);
+                let value = match target.is_single() {
+                    true => value.abs(),
+                    false => value,
+                };
+                let (min, max) = target.value_range();
+                morphs.push(Morph::new(target, value.clamp(min, max)));
+            }
+            // paired l-/r- morphs take the left side's value
+            morphs = symmetrize_morphs(&morphs, Side::Left);
+        }
+
+        // skin closest to the sampled ethnicity, gender and age
+        let dominant = (0..3)
+            .max_by(|a, b| ethnic[*a].total_cmp(&ethnic[*b]))
+            .unwrap_or(2);
+        let age_word = match age {
+            a if a < age_slider(40.0) => "young",
+            a if a < age_slider(65.0) => "middleage",
+            _ => "old",
+        };
+        let skins: Vec<SkinMaterial> = SkinMaterial::iter()
+            .filter(|s| is_gender(s, male))
+            .collect();
+        let matching: Vec<SkinMaterial> = skins
+            .iter()
+            .copied()
+            .filter(|s| {
+                let name = s.to_string().to_lowercase();
+                name.contains(&ETHNICITIES[dominant].to_lowercase()) && name.contains(age_word)
+            })
+            .collect();
+        let skin_material = pick(&mut rng, &matching)
+            .or_else(|| pick(&mut rng, &skins))
+            .unwrap_or_default();
+
+        let meshes: Vec<SkinMesh> = SkinMesh::iter()
+            .filter(|m| is_gender(m, male) && m.to_string().contains("Generic"))
+            .collect();
+        let skin_mesh = pick(&mut rng, &meshes).unwrap_or_default();
+
+        let clothing: Vec<Clothing> = Clothing::iter()
+            .filter(|c| {
+                self.clothing_tags.is_empty()
+                    || tags(c).any(|t| self.clothing_tags.iter().any(|a| a.eq_ignore_ascii_case(t)))
+            })
+            .collect();
+        let count = match self.outfit_size.is_empty() {
+            true => 0,
+            false => rng.random_range(self.outfit_size.clone()),
+        }
+        .min(clothing.len());
+        let outfit = clothing.choose_multiple(&mut rng, count).copied().collect();
+
+        let hair = match rng.random_bool(self.bald_chance.clamp(0.0, 1.0) as f64) {
+            true => None,
+            false => pick(&mut rng, &Hair::iter().collect::<Vec<_>>()),
+        };
+
+        HumanDescriptor {
+            skin_mesh,
+            skin_material,
+            eyes: pick(&mut rng, &Eyes::iter().collect::<Vec<_>>()).unwrap_or_default(),
+            hair,
+            eyebrows: pick(&mut rng, &Eyebrows::iter().collect::<Vec<_>>()).unwrap_or_default(),
+            eyelashes: pick(&mut rng, &Eyelashes::iter().collect::<Vec<_>>()).unwrap_or_default(),
+            outfit,
+            morphs,
+            macro_sliders: Some(sliders),
+            ..Default::default()
+        }
+    }
+
+    /// Most people sit near either end of the gender slider, resampled until inside [`Self::gender`]
+    fn sample_gender(&self, rng: &mut ChaCha8Rng) -> f32 {
+        if let Ok(normal) = Normal::new(0.0_f32, 0.1) {
+            for _ in 0..32 {
+                let spread = normal.sample(rng).abs();
+                let value = match rng.random_bool(0.5) {
+                    true => 1.0 - spread,
+                    false => spread,
+                };
+                if self.gender.contains(&value) {
+                    return value;
+                }
+            }
+        }
+        // range away from both ends, uniform instead
+        sample_range(rng, &self.gender)
+    }
+
+    /// One dominant ethnicity picked from the mix, with some admixture
+    fn sample_ethnicity(&self, rng: &mut ChaCha8Rng) -> [f32; 3] {
+        let total: f32 = self.ethnicity.iter().map(|e| e.max(0.0)).sum();
+        if total <= 0.0 {
+            return [1.0 / 3.0; 3];
+        }
+        let mix = self.ethnicity.map(|e| e.max(0.0) / total);
+        let mut roll = rng.random::<f32>();
+        let dominant = mix
+            .iter()
+            .position(|m| {
+                roll -= m;
+                roll < 0.0
+            })
+            .unwrap_or(2);
+        let admixture = rng.random_range(0.0..0.3);
+        let mut ethnic = mix.map(|m| m * admixture);
+        ethnic[dominant] += 1.0 - admixture;
+        ethnic
+    }
+}
+
+fn sample_range(rng: &mut ChaCha8Rng, range: &RangeInclusive<f32>) -> f32 {
+    match range.start() < range.end() {
+        true => rng.random_range(range.clone()),
+        false => *range.start(),
+    }
+}
+
+fn pick<T: Copy>(rng: &mut ChaCha8Rng, items: &[T]) -> Option<T> {
+    items.choose(rng).copied()
+}
+
+fn is_body_morph(target: &MorphTarget) -> bool {
+    if target.is_interpolated() {
+        return false;
+    }
+    target
+        .pos_path()
+        .or(target.neg_path())
+        .and_then(|p| Path::new(p).parent()?.file_name()?.to_str())
+        .is_some_and(|group| BODY_GROUPS.contains(&group))
+}
+
+/// Variant name says female, or male but not female
+fn is_gender(part: &impl Display, male: bool) -> bool {
+    let name = part.to_string().to_lowercase();
+    let female = name.contains("female");
+    match male {
+        true => !female && name.contains("male"),
+        false => female,
+    }
+}
+
+/// Tags from the part's mhclo, generated by build.rs
+pub fn tags<T: EnumProperty>(part: &T) -> impl Iterator<Item = &'static str> {
+    part.get_str("tags")
+        .into_iter()
+        .flat_map(|tags| tags.split(','))
+}
+
+/// Interpolate two humans, `t` 0 gives `a` and 1 gives `b`
+///
+/// Macros blend through their sliders, discrete parts come from the nearer parent
+pub fn blend_humans(a: &HumanDescriptor, b: &HumanDescriptor, t: f32) -> HumanDescriptor {
+    let t = t.clamp(0.0, 1.0);
+    let parent = if t < 0.5 { a } else { b };
+    let (morphs, macro_sliders) = mix_body(a, b, || t);
+    HumanDescriptor {
+        morphs,
+        macro_sliders,
+        clothing_offset: lerp(a.clothing_offset, b.clothing_offset, t),
+        floor_offset: lerp(a.floor_offset, b.floor_offset, t),
+        ..parent.clone()
+    }
+}
+
+/// Random child of two humans for `seed`, each morph a random mix of the parents and each part from either
+pub fn breed_humans(a: &HumanDescriptor, b: &HumanDescriptor, seed: u64) -> HumanDescriptor {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let (morphs, macro_sliders) = mix_body(a, b, || rng.random::<f32>());
+    // skin from the parent whose gender is closest to the child's
+    let child = macro_sliders.map_or_else(
+        || ethnic_macro_sliders(&morphs).map_or(0.5, |s| s.0),
+        |s| s.gender,
+    );
+    let skin = match (a.sliders().gender - child).abs() <= (b.sliders().gender - child).abs() {
+        true => a,
+        false => b,
+    };
+    let mut parent = || if rng.random_bool(0.5) { a } else { b };
+    HumanDescriptor {
+        name: None,
+        rig: parent().rig,
+        skin_mesh: skin.skin_mesh,
+        skin_material: skin.skin_material,
+        eyes: parent().eyes,
+        hair: parent().hair,
+        eyebrows: parent().eyebrows,
+        eyelashes: parent().eyelashes,
+        teeth: parent().teeth,
+        tongue: parent().tongue,
+        // whole outfits, mixing items may clash
+        outfit: parent().outfit.clone(),
+        morphs,
+        macro_sliders,
+        clothing_offset: parent().clothing_offset,
+        floor_offset: parent().floor_offset,
+    }
+}
+
+/// Lerp the macro sliders and every morph of either parent with a `t` per value
+///
+/// A morph only one parent has fades out with that parent's share, interpolated macros keep it as is
+fn mix_body(
+    a: &HumanDescriptor,
+    b: &HumanDescriptor,
+    mut t: impl FnMut() -> f32,
+) -> (Vec<Morph>, Option<MacroSliders>) {
+    let mut morphs = vec![];
+    let mut macro_sliders = None;
+    let (sliders_a, sliders_b) = (
+        ethnic_macro_sliders(&a.morphs),
+        ethnic_macro_sliders(&b.morphs),
+    );
+    if a.macro_sliders.is_some() || b.macro_sliders.is_some() {
+        let (sa, sb) = (a.sliders(), b.sliders());
+        let te = t();
+        let mut sliders = MacroSliders {
+            gender: lerp(sa.gender, sb.gender, t()),
+            age: lerp(sa.age, sb.age, t()),
+            muscle: lerp(sa.muscle, sb.muscle, t()),
+            weight: lerp(sa.weight, sb.weight, t()),
+            height: lerp(sa.height, sb.height, t()),
+            proportions: lerp(sa.proportions, sb.proportions, t()),
+            breast_size: lerp(sa.breast_size, sb.breast_size, t()),
+            breast_firmness: lerp(sa.breast_firmness, sb.breast_firmness, t()),
+            ..Default::default()
+        };
+        let (ea, eb) = (sa.ethnic(), sb.ethnic());
+        sliders.set_ethnic(std::array::from_fn(|i| lerp(ea[i], eb[i], te)));
+        macro_sliders = Some(sliders);
+    } else if sliders_a.is_some() || sliders_b.is_some() {
+        // makehuman defaults
+        let neutral = (0.5, 0.5, [1.0 / 3.0; 3]);
+        let (gender_a, age_a, ethnic_a) = sliders_a.unwrap_or(neutral);
+        let (gender_b, age_b, ethnic_b) = sliders_b.unwrap_or(neutral);
+        let gender = lerp(gender_a, gender_b, t());
+        let age = lerp(age_a, age_b, t());
+        let te = t();
+        let ethnic = std::array::from_fn(|i| lerp(ethnic_a[i], ethnic_b[i], te));
+        morphs.extend(ethnic_macro_morphs(gender, age, ethnic));
+    }
+
+    // ethnic macros are covered by the sliders above
+    let (a, b) = (&a.morphs, &b.morphs);
+    let mut targets: Vec<MorphTarget> = vec![];
+    for morph in a.iter().chain(b) {
+        if !is_ethnic_macro(&morph.target) && !targets.contains(&morph.target) {
+            targets.push(morph.target);
+        }
+    }
+    for target in targets {
+        let value = |morphs: &[Morph]| morphs.iter().find(|m| m.target == target).map(|m| m.value);
+        let value = match (value(a), value(b)) {
+            (Some(va), Some(vb)) => lerp(va, vb, t()),
+            // interpolated macros have no off value, keep the one parent's
+            (Some(v), None) | (None, Some(v)) if target.is_interpolated() => v,
+            // other targets are off at 0, fade with the parent's share
+            (Some(v), None) => lerp(v, 0.0, t()),
+            (None, Some(v)) => lerp(0.0, v, t()),
+            (None, None) => continue,
+        };
+        if !target.is_interpolated() && value.abs() < 0.001 {
+            continue;
+        }
+        let (min, max) = target.value_range();
+        morphs.push(Morph::new(target, value.clamp(min, max)));
+    }
+    (morphs, macro_sliders)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symmetry::{mirror_morphs, target_side};
+    use bevy::platform::collections::HashMap;
+
+    #[test]
+    fn test_generate_is_deterministic() {
+        let g = HumanGenerator::default();
+        assert_eq!(g.generate(7), g.generate(7));
+    }
+
+    #[test]
+    fn test_generate_seed_changes_human() {
+        let g = HumanGenerator::default();
+        assert_ne!(g.generate(7), g.generate(8));
+    }
+
+    #[test]
+    fn test_generate_empty_outfit_range() {
+        #[allow(clippy::reversed_empty_ranges)]
+        let g = HumanGenerator {
+            outfit_size: 3..=1,
+            ..Default::default()
+        };
+        assert!(g.generate(7).outfit.is_empty());
+    }
+
+    /// Same parts, morphs and sliders within float error, in any order, blending drops near 0 morphs
+    fn assert_same_human(x: &HumanDescriptor, y: &HumanDescriptor) {
+        let parts = |h: &HumanDescriptor| HumanDescriptor {
+            morphs: vec![],
+            macro_sliders: None,
+            ..h.clone()
+        };
+        assert_eq!(parts(x), parts(y));
+
+        let values = |h: &HumanDescriptor| {
+            h.morphs
+                .iter()
+                .filter(|m| m.value.abs() >= 0.001)
+                .map(|m| (m.target, m.value))
+                .collect::<HashMap<_, _>>()
+        };
+        let (vx, vy) = (values(x), values(y));
+        assert_eq!(vx.len(), vy.len());
+        for (target, v) in &vx {
+            assert!((v - vy[target]).abs() < 1e-5, "{target:?}");
+        }
+
+        let sliders = |h: &HumanDescriptor| {
+            let s = h.sliders();
+            [
+                s.gender,
+                s.age,
+                s.muscle,
+                s.weight,
+                s.height,
+                s.proportions,
+                s.african,
+                s.asian,
+                s.caucasian,
+                s.breast_size,
+                s.breast_firmness,
+            ]
+        };
+        for (a, b) in sliders(x).into_iter().zip(sliders(y)) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_blend_ends_are_parents() {
+        let g = HumanGenerator::default();
+        let (a, b) = (g.generate(1), g.generate(2));
+        assert_same_human(&blend_humans(&a, &b, 0.0), &a);
+        assert_same_human(&blend_humans(&a, &b, 1.0), &b);
+    }
+
+    #[test]
+    fn test_breed_is_deterministic() {
+        let g = HumanGenerator::default();
+        let (a, b) = (g.generate(1), g.generate(2));
+        let child = breed_humans(&a, &b, 3);
+        assert_eq!(child, breed_humans(&a, &b, 3));
+        assert!([a.rig, b.rig].contains(&child.rig));
+        assert!([a.eyes, b.eyes].contains(&child.eyes));
+        assert!(child.outfit == a.outfit || child.outfit == b.outfit);
+    }
+
+    #[test]
+    fn test_breed_picks_rig_from_either_parent() {
+        let a = HumanDescriptor {
+            rig: Rig::Mixamo,
+            ..Default::default()
+        };
+        let b = HumanDescriptor {
+            rig: Rig::Rigify,
+            ..Default::default()
+        };
+        let rigs: Vec<Rig> = (0..32).map(|seed| breed_humans(&a, &b, seed).rig).collect();
+        assert!(rigs.contains(&Rig::Mixamo) && rigs.contains(&Rig::Rigify));
+    }
+
+    #[test]
+    fn test_generate_gender_inside_range() {
+        let g = HumanGenerator {
+            gender: 0.2..=0.8,
+            ..Default::default()
+        };
+        let genders: Vec<f32> = (0..50)
+            .map(|seed| g.generate(seed).sliders().gender)
+            .collect();
+        assert!(genders.iter().all(|g| (0.2..=0.8).contains(g)));
+        // not piled up on the edges by clamping
+        assert!(!genders.iter().any(|g| *g == 0.2 || *g == 0.8));
+    }
+
+    #[test]
+    fn test_generate_paired_morphs_equal() {
+        let g = HumanGenerator {
+            body_morph_chance: 1.0,
+            ..Default::default()
+        };
+        let morphs = g.generate(7).morphs;
+        let values = |morphs: &[Morph]| {
+            morphs
+                .iter()
+                .map(|m| (m.target, m.value))
+                .collect::<HashMap<_, _>>()
+        };
+        assert!(morphs.iter().any(|m| target_side(&m.target).is_some()));
+        // symmetric, mirroring changes nothing
+        assert_eq!(values(&mirror_morphs(&morphs)), values(&morphs));
+    }
+}
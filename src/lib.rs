@@ -1,13 +1,22 @@
 pub mod assets;
+pub mod bake;
+pub mod cache;
 pub mod components;
 #[cfg(feature = "debug_draw")]
 pub mod debug_draw;
+pub mod export;
+pub mod generator;
 pub mod loaders;
+pub mod measure;
+pub mod scene;
 pub mod skeleton;
+pub mod symmetry;
 pub mod util;
 
 pub use crate::assets::MHThumb;
-use crate::{assets::*, components::*, loaders::*, skeleton::*, util::*};
+use crate::{
+    assets::*, bake::*, cache::*, components::*, loaders::*, measure::*, skeleton::*, util::*,
+};
 
 pub mod prelude {
     #[cfg(feature = "debug_draw")]
@@ -15,11 +24,13 @@ pub mod prelude {
 
     #[allow(unused_imports)]
     pub use crate::{
-        HumanComplete, MHState, MHThumb, MakeHumanPlugin, assets::*, components::*, loaders::*,
-        skeleton::*, util::*,
+        HumanComplete, HumanError, HumanFailed, MHHeadless, MHState, MHThumb, MakeHumanPlugin,
+        assets::*, bake::*, cache::MHCache, components::*, export::*, generator::*, loaders::*,
+        measure::*, scene::*, skeleton::*, symmetry::*, util::*,
     };
 }
 
+#[cfg(feature = "avian3d")]
 use avian3d::prelude::*;
 use bevy::animation::AnimatedBy;
 #[cfg(feature = "arkit")]
@@ -28,15 +39,29 @@ use bevy::asset::RenderAssetUsages;
 use bevy::mesh::morph::{MeshMorphWeights, MorphAttributes, MorphTargetImage};
 use bevy::{
     animation::AnimationTargetId,
-    mesh::skinning::{SkinnedMesh, SkinnedMeshInverseBindposes},
+    asset::{
+        AssetLoadError, AssetLoadFailedEvent, AssetPath, LoadState, RecursiveDependencyLoadState,
+    },
+    ecs::system::SystemParam,
+    mesh::{
+        Indices,
+        skinning::{SkinnedMesh, SkinnedMeshInverseBindposes},
+    },
+    platform::collections::HashMap,
     prelude::*,
+    state::app::StatesPlugin,
     tasks::{AsyncComputeTaskPool, Task, futures_lite::future},
 };
 use bevy_asset_loader::prelude::*;
 #[cfg(feature = "arkit")]
 use bevy_blend_shapes::ARKit;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 #[cfg(feature = "arkit")]
 use strum::IntoEnumIterator;
+use thiserror::Error;
 
 #[derive(Default, States, Debug, Clone, Eq, PartialEq, Hash, Reflect)]
 pub enum MHState {
@@ -52,22 +77,84 @@ pub struct HumanComplete {
     pub entity: Entity,
 }
 
-#[derive(Default)]
-pub struct MakeHumanPlugin;
+/// Trigger to notify Human generation failed, the human keeps its previous build
+/// and retries on the next [`HumanDirty`]
+#[derive(EntityEvent)]
+pub struct HumanFailed {
+    pub entity: Entity,
+    pub error: HumanError,
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum HumanError {
+    /// Asset or one of its dependencies failed to load
+    #[error("Failed to load {path}: {source}")]
+    AssetLoad {
+        path: String,
+        source: Arc<AssetLoadError>,
+    },
+    /// Asset reported loaded but is no longer in its collection
+    #[error("Asset {path} missing after load")]
+    AssetMissing { path: String },
+}
+
+/// Add with `MakeHumanPlugin::default()`, or set the fields to change caching and headless mode
+pub struct MakeHumanPlugin {
+    /// Directory to cache prepared basemesh data in, `None` to rebuild every launch
+    ///
+    /// Defaults to `bevy_make_human` in the system temp dir, `None` on wasm which has no filesystem
+    pub cache_dir: Option<PathBuf>,
+    /// Only build skeleton, height and collider, no meshes, materials or textures
+    ///
+    /// For dedicated servers, works under `MinimalPlugins`
+    pub headless: bool,
+}
+
+impl Default for MakeHumanPlugin {
+    fn default() -> Self {
+        Self {
+            cache_dir: match cfg!(target_arch = "wasm32") {
+                true => None,
+                false => Some(std::env::temp_dir().join("bevy_make_human")),
+            },
+            headless: false,
+        }
+    }
+}
+
+/// Present when running headless, see [`MakeHumanPlugin::headless`]
+#[derive(Resource, Default)]
+pub struct MHHeadless;
 
 impl Plugin for MakeHumanPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((
+        if self.headless {
+            // MinimalPlugins has neither
+            if !app.is_plugin_added::<AssetPlugin>() {
+                app.add_plugins(AssetPlugin::default());
+            }
+            if !app.is_plugin_added::<StatesPlugin>() {
+                app.add_plugins(StatesPlugin);
+            }
+            app.init_resource::<MHHeadless>();
+        } else {
+            // mhmat to material loader
+            app.init_asset_loader::<MhmatLoader>() // -> StandardMaterial
+                // thumb image loader (PNG thumbnails)
+                .init_asset_loader::<ThumbLoader>(); // -> Image
             #[cfg(feature = "debug_draw")]
-            debug_draw::MakeHumanDebugPlugin,
-        ))
+            app.add_plugins(debug_draw::MakeHumanDebugPlugin);
+        }
+
+        app.insert_resource(MHCache {
+            dir: self.cache_dir.clone(),
+        })
         .init_state::<MHState>()
         .add_loading_state(
             LoadingState::new(MHState::LoadingAssets)
                 .load_collection::<BaseMeshAssets>()
                 .continue_to_state(MHState::LoadingBasemesh),
         )
-        // TODO: save this work out instead of rebuilding every time
         .add_systems(OnEnter(MHState::LoadingBasemesh), build_basemesh)
         .add_systems(
             Update,
@@ -87,6 +174,26 @@ impl Plugin for MakeHumanPlugin {
             )
                 .run_if(in_state(MHState::Ready)),
         );
+        // presets can expand before the basemesh is ready
+        app.add_systems(Update, apply_human_presets.before(dirty_check));
+        app.add_systems(
+            Update,
+            (
+                start_measurement_fits,
+                run_measurement_fits,
+                finish_measurement_fits,
+            )
+                .chain()
+                .before(dirty_check)
+                .run_if(in_state(MHState::Ready)),
+        );
+        #[cfg(feature = "avian3d")]
+        app.register_type::<HumanRigidBody>().add_systems(
+            Update,
+            update_human_physics
+                .after(update_human)
+                .run_if(in_state(MHState::Ready)),
+        );
 
         // asset loaders
         app
@@ -99,12 +206,21 @@ impl Plugin for MakeHumanPlugin {
             // morph target loader
             .init_asset::<MorphTargetData>()
             .init_asset_loader::<MorphTargetLoader>()
+            // vertex mirror table loader
+            .init_asset::<MirrorTable>()
+            .init_asset_loader::<MirrorTableLoader>()
             // // faceshapes (.mxa) loader for FACS expressions
             // .init_asset::<FaceshapesData>()
             // .init_asset_loader::<FaceshapesLoader>()
             // mhclo loader
             .init_asset::<MhcloAsset>()
             .init_asset_loader::<MhcloLoader>()
+            // .mhm character loader
+            .init_asset::<MhmAsset>()
+            .init_asset_loader::<MhmLoader>()
+            // .human.ron/.human.json preset loader
+            .init_asset::<HumanDescriptor>()
+            .init_asset_loader::<HumanDescriptorLoader>()
             // proxy mesh loader
             .init_asset::<ProxyAsset>()
             .init_asset_loader::<ProxyLoader>()
@@ -114,10 +230,6 @@ impl Plugin for MakeHumanPlugin {
             // skinning weights
             .init_asset::<SkinningWeights>()
             .init_asset_loader::<SkinningWeightsLoader>()
-            // mhmat to material loader
-            .init_asset_loader::<MhmatLoader>() // -> StandardMaterial
-            // thumb image loader (PNG thumbnails)
-            .init_asset_loader::<ThumbLoader>() // -> Image
             // bvh pose loader
             .init_asset::<Pose>()
             .init_asset_loader::<BvhPoseLoader>()
@@ -126,7 +238,25 @@ impl Plugin for MakeHumanPlugin {
             .register_type::<MHTag>()
             .register_type::<ClothingOffset>()
             .register_type::<FloorOffset>()
-            .register_type::<Morph>();
+            .register_type::<Morph>()
+            .register_type::<HumanBuildProgress>()
+            // scene (de)serialization, see scene::human_scene
+            .register_type::<Human>()
+            .register_type::<HumanDirty>()
+            .register_type::<Morphs>()
+            .register_type::<MacroSliders>()
+            .register_type::<SkinInfluences>()
+            .register_type::<HumanMeasurements>()
+            .register_type::<MeasurementGoal>()
+            .register_type::<Rig>()
+            .register_type::<SkinMesh>()
+            .register_type::<SkinMaterial>()
+            .register_type::<Eyes>()
+            .register_type::<Eyebrows>()
+            .register_type::<Eyelashes>()
+            .register_type::<Teeth>()
+            .register_type::<Tongue>()
+            .register_type::<Hair>();
     }
 }
 
@@ -136,6 +266,8 @@ pub struct BaseMeshAssets {
     pub obj: Handle<ObjBaseMesh>,
     #[asset(path = "make_human/mesh_metadata/basemesh.vertex_groups.json")]
     pub vertex_groups: Handle<VertexGroups>,
+    #[asset(path = "make_human/mesh_metadata/hm08.mirror")]
+    pub mirror: Handle<MirrorTable>,
     // TODO: will most likely need this later
     // #[asset(path = "make_human/mesh_metadata/hm08_config.json")]
     // pub config: Handle<BasemeshConfig>,
@@ -151,6 +283,8 @@ pub struct BaseMesh {
     pub mhid_lookup: Vec<u16>,
     /// Vertex groups for bone CUBE/MEAN strategies
     pub vertex_groups: VertexGroups,
+    /// Vertex symmetry table for mirroring morph targets
+    pub mirror: MirrorTable,
 }
 
 #[derive(Resource)]
@@ -164,16 +298,32 @@ fn build_basemesh(
     mut commands: Commands,
     base_mesh_assets: Res<BaseMeshAssets>,
     obj_assets: ResMut<Assets<ObjBaseMesh>>,
+    cache: Res<MHCache>,
 ) {
     // grab copy for async task
     let obj_base_mesh = obj_assets.get(&base_mesh_assets.obj).unwrap().clone();
+    let cache_path = cache.file(BASEMESH_CACHE_FILE);
 
     let task = AsyncComputeTaskPool::get().spawn(async move {
+        // Reuse cached lookup if base.obj is unchanged
+        if let Some(path) = &cache_path
+            && let Some(mhid_lookup) = read_mhid_lookup(path, obj_base_mesh.hash)
+        {
+            debug!("Loaded basemesh cache from {:?}", path);
+            return PrepareBasemeshOutput { mhid_lookup };
+        }
+
         // Get mesh and vertex map and build mhid_lookup, takes 220ms
         let vtx_data = get_vertex_positions(&obj_base_mesh.mesh);
         let vertex_map = generate_vertex_map(&obj_base_mesh.vertices, &vtx_data);
         let mhid_lookup = generate_mhid_lookup(&vertex_map);
 
+        if let Some(path) = &cache_path
+            && let Err(e) = write_mhid_lookup(path, obj_base_mesh.hash, &mhid_lookup)
+        {
+            warn!("Failed to write basemesh cache {:?}: {}", path, e);
+        }
+
         PrepareBasemeshOutput { mhid_lookup }
     });
     commands.insert_resource(PrepareBasemeshTask(task));
@@ -184,8 +334,9 @@ fn poll_basemesh_task(
     base_mesh_assets: Res<BaseMeshAssets>,
     obj_assets: ResMut<Assets<ObjBaseMesh>>,
     vg_assets: Res<Assets<VertexGroups>>,
+    mirror_assets: Res<Assets<MirrorTable>>,
     mut prepare_task: ResMut<PrepareBasemeshTask>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    meshes: Option<ResMut<Assets<Mesh>>>,
 ) {
     if let Some(PrepareBasemeshOutput { mhid_lookup }) =
         future::block_on(future::poll_once(&mut prepare_task.0))
@@ -200,11 +351,20 @@ fn poll_basemesh_task(
             .expect("vg loaded")
             .clone();
 
+        let mirror = mirror_assets
+            .get(&base_mesh_assets.mirror)
+            .expect("mirror loaded")
+            .clone();
+
         commands.insert_resource(BaseMesh {
-            _mesh: meshes.add(obj_base_mesh.mesh.clone()),
+            // headless apps have no mesh store
+            _mesh: meshes
+                .map(|mut meshes| meshes.add(obj_base_mesh.mesh.clone()))
+                .unwrap_or_default(),
             vertices: obj_base_mesh.vertices.clone(),
             mhid_lookup,
             vertex_groups: vg.clone(),
+            mirror,
             ..default()
         });
         commands.remove_resource::<BaseMeshAssets>();
@@ -212,6 +372,40 @@ fn poll_basemesh_task(
     }
 }
 
+/// Expand [`HumanPreset`]s into human components, again whenever the descriptor is modified
+///
+/// A descriptor that fails to load fails the human with [`HumanFailed`]
+fn apply_human_presets(
+    mut commands: Commands,
+    mut events: MessageReader<AssetEvent<HumanDescriptor>>,
+    mut failed: MessageReader<AssetLoadFailedEvent<HumanDescriptor>>,
+    descriptors: Res<Assets<HumanDescriptor>>,
+    asset_server: Res<AssetServer>,
+    mut presets: Query<(Entity, Ref<HumanPreset>, &mut HumanBuildProgress)>,
+) {
+    let changed: Vec<AssetId<HumanDescriptor>> = events
+        .read()
+        .filter_map(|e| match e {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .chain(failed.read().map(|e| e.id))
+        .collect();
+
+    for (entity, preset, mut progress) in &mut presets {
+        if !preset.is_changed() && !changed.contains(&preset.id()) {
+            continue;
+        }
+        // not loaded yet, LoadedWithDependencies or AssetLoadFailedEvent will get it
+        if let Some(descriptor) = descriptors.get(&preset.0) {
+            descriptor.insert(&mut commands.entity(entity));
+        } else if let Some(error) = failed_load(&asset_server, &[preset.0.clone().untyped()]) {
+            *progress = HumanBuildProgress::Failed;
+            commands.trigger(HumanFailed { entity, error });
+        }
+    }
+}
+
 // TODO: yea changed check is slow, replace with observers on change maybe?
 /// mark Human as dirty when relevant components change
 fn dirty_check(
@@ -231,15 +425,22 @@ fn dirty_check(
             Changed<Outfit>,
             Changed<ClothingOffset>,
             Changed<Morphs>,
-            Changed<FloorOffset>,
+            Changed<MacroSliders>,
+            Changed<SkinInfluences>,
         )>,
     >,
     mut removed_hair: RemovedComponents<Hair>,
+    mut removed_sliders: RemovedComponents<MacroSliders>,
+    mut removed_influences: RemovedComponents<SkinInfluences>,
 ) {
     for e in query.iter() {
         commands.entity(e).insert(HumanDirty);
     }
-    for e in removed_hair.read() {
+    for e in removed_hair
+        .read()
+        .chain(removed_sliders.read())
+        .chain(removed_influences.read())
+    {
         if let Ok(mut ec) = commands.get_entity(e) {
             ec.insert(HumanDirty);
         }
@@ -248,10 +449,52 @@ fn dirty_check(
 
 /// Task component for async character processing
 #[derive(Component)]
-pub struct HumanProcessingTask(Task<HumanProcessingOutput>);
+pub struct HumanProcessingTask {
+    task: Task<Option<HumanProcessingOutput>>,
+    token: HumanBuildToken,
+    progress: SharedProgress,
+}
+
+/// Progress written by the async build, copied to [`HumanBuildProgress`] each frame
+type SharedProgress = Arc<Mutex<HumanBuildProgress>>;
+
+fn set_progress(progress: &SharedProgress, stage: HumanBuildProgress) {
+    if let Ok(mut p) = progress.lock() {
+        *p = stage;
+    }
+}
 
 /// All data needed for human processing (extracted from assets)
 struct HumanProcessingInput {
+    token: HumanBuildToken,
+    progress: SharedProgress,
+    body: HumanBodyInput,
+    skinning_weights: SkinningWeights,
+    /// [`REFERENCE_RIG`] body weights, None when the human uses it
+    reference_weights: Option<SkinningWeights>,
+
+    // Parts
+    parts: Vec<MHItemLoaded>,
+
+    clothing_offset: f32,
+    /// Sorted base mesh vertices hidden under the worn clothing
+    deleted_vertices: Vec<u32>,
+    /// z_depths of the worn clothing, see [`clothing_layers`]
+    clothing_layers: Vec<u32>,
+    influences: SkinInfluences,
+    stale_parts: Vec<Entity>,
+}
+
+enum HumanBodyInput {
+    /// Morph the base mesh, build the skeleton and fit the skin
+    Build(Box<HumanBodyBuildInput>),
+    /// Reuse morphed vertices and skeleton from the last build
+    Cached(BakedBody),
+}
+
+struct HumanBodyBuildInput {
+    source: HumanBodySource,
+
     base_vertices: Vec<Vec3>,
     base_vertex_groups: VertexGroups,
 
@@ -259,312 +502,660 @@ struct HumanProcessingInput {
     morphs: Vec<(MorphTargetData, f32)>,
     // Rig
     rig_bones: RigBones,
-    skinning_weights: SkinningWeights,
-
-    // Skin proxy
-    skin_proxy: (ProxyAsset, ObjBaseMesh),
-    skin_material: Handle<StandardMaterial>,
-
-    // Parts
-    parts: Vec<MHItemLoaded>,
 
-    clothing_offset: f32,
-
-    #[cfg(feature = "arkit")]
-    /// ARKit blend shape targets (52 shapes)
-    arkit_targets: Vec<MorphTargetData>,
+    /// None in headless mode
+    skin: Option<(BakeSkin, Handle<StandardMaterial>)>,
 }
 
 /// Result of human processing
 struct HumanProcessingOutput {
-    skeleton: Skeleton,
+    generation: u64,
+    /// Rebuilt body, None when only parts changed
+    body: Option<HumanBodyOutput>,
     parts: Vec<MHItemResult>,
-    /// Height (max_y - min_y of morphed vertices)
-    height: f32,
-    /// Min Y of morphed vertices (for ground offset)
-    min_y: f32,
-    #[cfg(feature = "arkit")]
-    /// ARKit morph deltas transferred to proxy mesh (52 x vertex_count)
-    arkit_morphs: Vec<Vec<Vec3>>,
+    clothing_offset: f32,
+    deleted_vertices: Vec<u32>,
+    stale_parts: Vec<Entity>,
+}
+
+struct HumanBodyOutput {
+    source: HumanBodySource,
+    body: BakedBody,
+    /// None in headless mode
+    skin: Option<(BakedSkin, Handle<StandardMaterial>)>,
 }
 
 fn human_changed(
     mut commands: Commands,
-    query: Query<HumanQuery, With<HumanDirty>>,
+    mut query: Query<
+        (
+            HumanQuery,
+            &HumanGeneration,
+            &mut HumanBuildProgress,
+            Option<&mut HumanBuildCache>,
+        ),
+        With<HumanDirty>,
+    >,
     asset_server: Res<AssetServer>,
+    headless: Option<Res<MHHeadless>>,
 ) {
-    for h in query.iter() {
-        let mut parts = vec![];
-        if let Some(hair_item) = h.hair {
-            parts.push(MHItem::load(MHTag::Hair, hair_item, &asset_server));
-        }
-        parts.push(MHItem::load(MHTag::Eyes, h.eyes, &asset_server));
-        parts.push(MHItem::load(MHTag::Eyebrows, h.eyebrows, &asset_server));
-        parts.push(MHItem::load(MHTag::Eyelashes, h.eyelashes, &asset_server));
-        parts.push(MHItem::load(MHTag::Teeth, h.teeth, &asset_server));
-        parts.push(MHItem::load(MHTag::Tongue, h.tongue, &asset_server));
-
-        for clothing_item in h.clothing.iter() {
-            parts.push(MHItem::load(MHTag::Clothes, clothing_item, &asset_server));
-        }
+    for (h, generation, mut progress, cache) in query.iter_mut() {
+        // headless humans have no parts
+        let mut sources = vec![];
+        if headless.is_none() {
+            if let Some(hair_item) = h.hair {
+                sources.push(MHPartSource::new(MHTag::Hair, hair_item, *h.rig));
+            }
+            sources.push(MHPartSource::new(MHTag::Eyes, h.eyes, *h.rig));
+            sources.push(MHPartSource::new(MHTag::Eyebrows, h.eyebrows, *h.rig));
+            sources.push(MHPartSource::new(MHTag::Eyelashes, h.eyelashes, *h.rig));
+            sources.push(MHPartSource::new(MHTag::Teeth, h.teeth, *h.rig));
+            sources.push(MHPartSource::new(MHTag::Tongue, h.tongue, *h.rig));
 
-        // Load all morph targets (unified - body morphs + macro morphs)
-        // For interpolated macro morphs, load min/avg/max with interpolated weights
-        let mut morphs: Vec<(Handle<MorphTargetData>, f32)> = Vec::new();
-
-        for Morph { target, value } in h.morphs.iter() {
-            if target.is_interpolated() {
-                // Interpolated macro morph: value 0..1 maps to min->avg->max
-                if let Some((min_path, avg_path, max_path)) = target.macro_paths() {
-                    let v = value.clamp(0.0, 1.0);
-                    if v < 0.5 {
-                        // Blend min -> avg
-                        let t = v / 0.5;
-                        if let Some(min) = min_path {
-                            morphs.push((asset_server.load(min.to_string()), 1.0 - t));
-                        }
-                        if let Some(avg) = avg_path {
-                            morphs.push((asset_server.load(avg.to_string()), t));
-                        }
-                    } else {
-                        // Blend avg -> max
-                        let t = (v - 0.5) / 0.5;
-                        if let Some(avg) = avg_path {
-                            morphs.push((asset_server.load(avg.to_string()), 1.0 - t));
-                        }
-                        if let Some(max) = max_path {
-                            morphs.push((asset_server.load(max.to_string()), t));
-                        }
-                    }
-                }
-            } else if let Some(path) = target.target_path(*value) {
-                // Simple morph - single path
-                morphs.push((asset_server.load(path.to_string()), value.abs()));
+            for clothing_item in h.clothing.iter() {
+                sources.push(MHPartSource::new(MHTag::Clothes, clothing_item, *h.rig));
             }
         }
 
-        #[cfg(feature = "arkit")]
-        let arkit_targets: Vec<Handle<MorphTargetData>> = ARKit::iter()
-            .map(|shape| asset_server.load(format!("make_human/targets/arkit/{}.target", shape)))
+        let clothes: Vec<Handle<MhcloAsset>> = sources
+            .iter()
+            .filter(|s| s.tag == MHTag::Clothes)
+            .map(|s| asset_server.load(s.mhclo.to_string()))
             .collect();
 
-        commands
-            .entity(h.entity)
-            .remove::<HumanDirty>()
-            // TODO: cancel existing task?
-            .remove::<HumanProcessingTask>() // stop current builds,
-            .insert(HumanAssets {
-                skin_obj_base: asset_server.load(h.skin_mesh.obj().to_string()),
-                skin_proxy: asset_server.load(h.skin_mesh.proxy().to_string()),
-                skin_material: asset_server.load(h.skin_material.mhmat().to_string()),
+        let body_source = HumanBodySource {
+            rig: *h.rig,
+            skin_mesh: *h.skin_mesh,
+            skin_material: *h.skin_material,
+            morphs: h.morphs.clone(),
+            macro_sliders: h.macro_sliders.copied(),
+            influences: h.skin_influences.map(|i| i.validated()).unwrap_or_default(),
+        };
+
+        // in-flight builds see they're stale and stop, any pending HumanAssets is replaced
+        let generation = generation.bump();
+
+        let mut ec = commands.entity(h.entity);
+        ec.remove::<HumanDirty>();
+
+        // Body unchanged since last build, only redo what changed
+        if let Some(mut cache) = cache
+            && !cache.body.needs_rebuild(&body_source)
+        {
+            if cache.body.skin_material != body_source.skin_material && headless.is_none() {
+                ec.insert(MeshMaterial3d::<StandardMaterial>(
+                    asset_server.load(h.skin_material.mhmat().to_string()),
+                ));
+                cache.body.skin_material = body_source.skin_material;
+            }
+
+            // Clothes are refit when the offset or the outfit changes, layers may have shifted
+            let clothing_offset = h.clothing_offset.0;
+            let is_clothes = |s: &MHPartSource| s.tag == MHTag::Clothes;
+            let outfit_changed = sources.iter().filter(|s| is_clothes(s)).count()
+                != cache.parts.iter().filter(|(s, _)| is_clothes(s)).count()
+                || sources
+                    .iter()
+                    .filter(|s| is_clothes(s))
+                    .any(|s| !cache.parts.iter().any(|(built, _)| built == s));
+            let refit = |s: &MHPartSource| {
+                is_clothes(s) && (cache.clothing_offset != clothing_offset || outfit_changed)
+            };
+
+            let parts = sources
+                .iter()
+                .filter(|&s| refit(s) || !cache.parts.iter().any(|(built, _)| built == s))
+                .map(|s| MHItem::load_source(*s, &asset_server))
+                .collect::<Vec<_>>();
+            let stale_parts = cache
+                .parts
+                .iter()
+                .filter(|(built, _)| refit(built) || !sources.contains(built))
+                .map(|(_, e)| *e)
+                .collect::<Vec<_>>();
+
+            if parts.is_empty() && stale_parts.is_empty() {
+                // applied in place above
+                *progress = HumanBuildProgress::Complete;
+                commands.trigger(HumanComplete { entity: h.entity });
+            } else {
+                *progress = HumanBuildProgress::Loading {
+                    loaded: 0,
+                    total: 0,
+                };
+                commands.entity(h.entity).insert(HumanAssets {
+                    generation,
+                    body: None,
+                    rig_weights: asset_server.load(h.rig.weights().to_string()),
+                    reference_weights: reference_weights(*h.rig, &asset_server),
+                    clothing_offset,
+                    influences: body_source.influences,
+                    parts,
+                    clothes,
+                    stale_parts,
+                });
+            }
+            continue;
+        }
+
+        // Load all morph targets (unified - body morphs + macro morphs)
+        let morphs: Vec<(Handle<MorphTargetData>, f32)> =
+            morph_target_weights(&h.morphs, h.macro_sliders)
+                .into_iter()
+                .map(|(path, weight)| (asset_server.load(path), weight))
+                .collect();
+
+        let skin = headless.is_none().then(|| HumanSkinAssets {
+            obj_base: asset_server.load(h.skin_mesh.obj().to_string()),
+            proxy: asset_server.load(h.skin_mesh.proxy().to_string()),
+            material: asset_server.load(h.skin_material.mhmat().to_string()),
+            #[cfg(feature = "arkit")]
+            arkit_targets: ARKit::iter()
+                .map(|shape| {
+                    asset_server.load(format!("make_human/targets/arkit/{}.target", shape))
+                })
+                .collect(),
+        });
+
+        *progress = HumanBuildProgress::Loading {
+            loaded: 0,
+            total: 0,
+        };
+        commands.entity(h.entity).insert(HumanAssets {
+            generation,
+            influences: body_source.influences,
+            body: Some(HumanBodyAssets {
+                source: body_source,
+                skin,
                 rig_bones: asset_server.load(h.rig.rig_json_path().to_string()),
-                rig_weights: asset_server.load(h.rig.weights().to_string()),
-                clothing_offset: h.clothing_offset.0,
-                parts,
                 morphs,
-                #[cfg(feature = "arkit")]
-                arkit_targets,
-            });
+            }),
+            rig_weights: asset_server.load(h.rig.weights().to_string()),
+            reference_weights: reference_weights(*h.rig, &asset_server),
+            clothing_offset: h.clothing_offset.0,
+            parts: sources
+                .iter()
+                .map(|s| MHItem::load_source(*s, &asset_server))
+                .collect(),
+            clothes,
+            // full rebuild replaces all children
+            stale_parts: vec![],
+        });
     }
 }
 
+/// [`REFERENCE_RIG`] body weights, to remap part weights made for it onto `rig`
+fn reference_weights(rig: Rig, asset_server: &AssetServer) -> Option<Handle<SkinningWeights>> {
+    (rig != REFERENCE_RIG).then(|| asset_server.load(REFERENCE_RIG.weights().to_string()))
+}
+
+/// Asset collections a human build reads from
+#[derive(SystemParam)]
+struct HumanAssetData<'w> {
+    base_mesh: Res<'w, BaseMesh>,
+    mhclo: Res<'w, Assets<MhcloAsset>>,
+    proxy: Res<'w, Assets<ProxyAsset>>,
+    obj_base: Res<'w, Assets<ObjBaseMesh>>,
+    rig_bones: Res<'w, Assets<RigBones>>,
+    skinning_weights: Res<'w, Assets<SkinningWeights>>,
+    morph_targets: Res<'w, Assets<MorphTargetData>>,
+}
+
+impl HumanAssetData<'_> {
+    /// Extract everything the task needs, None if the build can't run from this plan
+    fn processing_input(
+        &self,
+        assets: &HumanAssets,
+        cache: Option<&HumanBuildCache>,
+        token: HumanBuildToken,
+        progress: SharedProgress,
+    ) -> Result<Option<HumanProcessingInput>, HumanError> {
+        let parts = assets
+            .parts
+            .iter()
+            .map(|a| -> Result<_, HumanError> {
+                let clo = loaded(&self.mhclo, &a.clo)?.clone();
+                // a .mhw shipped next to the mhclo, else the one the mhclo names
+                let (weights, reference_weights) = match &a.weights {
+                    Some(h) => (
+                        Some(h),
+                        matches!(a.source.weights, Some(PartWeightsFile::Reference(_))),
+                    ),
+                    None => (clo.vertex_bone_weights.as_ref(), true),
+                };
+                Ok(MHItemLoaded {
+                    source: a.source,
+                    base: loaded(&self.obj_base, &a.obj_base)?.clone(),
+                    mat: a.mat.clone(),
+                    weights: weights
+                        .map(|h| loaded(&self.skinning_weights, h).cloned())
+                        .transpose()?,
+                    reference_weights,
+                    clo,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let body = match (&assets.body, cache) {
+            (Some(body), _) => {
+                // Build skin proxy data
+                let skin = match &body.skin {
+                    Some(skin) => Some((
+                        BakeSkin {
+                            proxy: loaded(&self.proxy, &skin.proxy)?.clone(),
+                            obj_base: loaded(&self.obj_base, &skin.obj_base)?.clone(),
+                            #[cfg(feature = "arkit")]
+                            arkit_targets: skin
+                                .arkit_targets
+                                .iter()
+                                .map(|h| loaded(&self.morph_targets, h).cloned())
+                                .collect::<Result<Vec<_>, _>>()?,
+                        },
+                        skin.material.clone(),
+                    )),
+                    None => None,
+                };
+
+                HumanBodyInput::Build(Box::new(HumanBodyBuildInput {
+                    source: body.source.clone(),
+                    base_vertices: self.base_mesh.vertices.clone(),
+                    base_vertex_groups: self.base_mesh.vertex_groups.clone(),
+                    morphs: body
+                        .morphs
+                        .iter()
+                        .map(|(h, w)| -> Result<_, HumanError> {
+                            Ok((loaded(&self.morph_targets, h)?.clone(), *w))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                    rig_bones: loaded(&self.rig_bones, &body.rig_bones)?.clone(),
+                    skin,
+                }))
+            }
+            (None, Some(cache)) => HumanBodyInput::Cached(BakedBody {
+                morphed_vertices: cache.morphed_vertices.clone(),
+                skeleton: cache.skeleton.clone(),
+                height: cache.height,
+                min_y: cache.min_y,
+                radius: cache.radius,
+                measurements: cache.measurements,
+            }),
+            // cache removed since this build was planned
+            (None, None) => return Ok(None),
+        };
+
+        let clothes = assets
+            .clothes
+            .iter()
+            .map(|h| loaded(&self.mhclo, h))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(HumanProcessingInput {
+            token,
+            progress,
+            body,
+            skinning_weights: loaded(&self.skinning_weights, &assets.rig_weights)?.clone(),
+            reference_weights: assets
+                .reference_weights
+                .as_ref()
+                .map(|h| loaded(&self.skinning_weights, h).cloned())
+                .transpose()?,
+            clothing_offset: assets.clothing_offset,
+            deleted_vertices: deleted_vertices(clothes.iter().copied()),
+            clothing_layers: clothing_layers(clothes.iter().copied()),
+            influences: assets.influences,
+            stale_parts: assets.stale_parts.clone(),
+            parts,
+        }))
+    }
+}
+
+/// Get a loaded asset, erroring instead of panicking if it's gone
+fn loaded<'a, A: Asset>(assets: &'a Assets<A>, handle: &Handle<A>) -> Result<&'a A, HumanError> {
+    assets.get(handle).ok_or_else(|| HumanError::AssetMissing {
+        path: asset_path(handle.path(), handle.id()),
+    })
+}
+
+/// Path for error messages, falls back to the id for unnamed assets
+fn asset_path(path: Option<&AssetPath>, id: impl std::fmt::Debug) -> String {
+    path.map(|p| p.to_string())
+        .unwrap_or_else(|| format!("{:?}", id))
+}
+
+/// First failed load among `handles`, including their dependencies
+fn failed_load(asset_server: &AssetServer, handles: &[UntypedHandle]) -> Option<HumanError> {
+    handles.iter().find_map(|h| {
+        let error = match asset_server.load_state(h.id()) {
+            LoadState::Failed(error) => error,
+            _ => match asset_server.get_recursive_dependency_load_state(h.id()) {
+                Some(RecursiveDependencyLoadState::Failed(error)) => error,
+                _ => return None,
+            },
+        };
+        Some(HumanError::AssetLoad {
+            path: asset_path(h.path(), h.id()),
+            source: error,
+        })
+    })
+}
+
 fn loading_human_assets(
     mut commands: Commands,
-    mut query: Query<(Entity, &HumanAssets)>,
+    // wait for running builds to finish so only one build per human runs at a time
+    mut query: Query<
+        (
+            Entity,
+            &HumanAssets,
+            &HumanGeneration,
+            &mut HumanBuildProgress,
+            Option<&HumanBuildCache>,
+        ),
+        Without<HumanProcessingTask>,
+    >,
     asset_server: Res<AssetServer>,
-    base_mesh: Res<BaseMesh>,
-    mhclo_assets: Res<Assets<MhcloAsset>>,
-    proxy_assets: Res<Assets<ProxyAsset>>,
-    obj_base_assets: Res<Assets<ObjBaseMesh>>,
-    rig_bones_assets: Res<Assets<RigBones>>,
-    skinning_weights_assets: Res<Assets<SkinningWeights>>,
-    morph_target_assets: Res<Assets<MorphTargetData>>,
+    data: HumanAssetData,
 ) {
-    for (e, assets) in query.iter_mut() {
+    for (e, assets, generation, mut progress, cache) in query.iter_mut() {
+        // superseded by a newer change that didn't need a build
+        if assets.generation != generation.current() {
+            commands.entity(e).remove::<HumanAssets>();
+            continue;
+        }
+
         let handles = assets.all_handles();
+
+        // Give up on this build, the human keeps whatever it had before
+        if let Some(error) = failed_load(&asset_server, &handles) {
+            *progress = HumanBuildProgress::Failed;
+            commands.entity(e).remove::<HumanAssets>();
+            commands.trigger(HumanFailed { entity: e, error });
+            continue;
+        }
+
         let total = handles.len();
         let loaded = handles
             .iter()
             .filter(|h| asset_server.is_loaded_with_dependencies(h.id()))
             .count();
+        progress.set_if_neq(HumanBuildProgress::Loading { loaded, total });
+        if loaded < total {
+            continue;
+        }
 
-        if loaded >= total {
-            let parts = assets
-                .parts
-                .iter()
-                .map(|a| MHItemLoaded {
-                    tag: a.tag,
-                    base: obj_base_assets.get(&a.obj_base).unwrap().clone(),
-                    mat: a.mat.clone(),
-                    clo: mhclo_assets.get(&a.clo).unwrap().clone(),
-                })
-                .collect::<Vec<_>>();
+        let token = generation.token(assets.generation);
+        let shared = SharedProgress::default();
+        match data.processing_input(assets, cache, token.clone(), shared.clone()) {
+            Ok(Some(input)) => {
+                // Spawn async task
+                let task = AsyncComputeTaskPool::get().spawn(async move { process_human(input) });
+                commands
+                    .entity(e)
+                    .remove::<HumanAssets>()
+                    .insert(HumanProcessingTask {
+                        task,
+                        token,
+                        progress: shared,
+                    });
+            }
+            Ok(None) => {
+                // cache removed since this build was planned, start over
+                commands
+                    .entity(e)
+                    .remove::<HumanAssets>()
+                    .insert(HumanDirty);
+            }
+            Err(error) => {
+                *progress = HumanBuildProgress::Failed;
+                commands.entity(e).remove::<HumanAssets>();
+                commands.trigger(HumanFailed { entity: e, error });
+            }
+        }
+    }
+}
 
-            // Build skin proxy data
-            let skin_proxy = (
-                proxy_assets.get(&assets.skin_proxy).unwrap().clone(),
-                obj_base_assets.get(&assets.skin_obj_base).unwrap().clone(),
-            );
+/// Returns None if the build went stale before finishing
+fn process_human(input: HumanProcessingInput) -> Option<HumanProcessingOutput> {
+    let token = input.token;
+    if token.is_stale() {
+        return None;
+    }
 
-            #[cfg(feature = "arkit")]
-            let arkit_targets: Vec<MorphTargetData> = assets
-                .arkit_targets
+    let (body, baked) = match input.body {
+        HumanBodyInput::Build(body) => {
+            let body = build_body(
+                *body,
+                &input.skinning_weights,
+                &input.deleted_vertices,
+                &input.influences,
+                &token,
+                &input.progress,
+            )?;
+            let baked = body.body.clone();
+            (Some(body), baked)
+        }
+        HumanBodyInput::Cached(baked) => (None, baked),
+    };
+
+    // where reference rig bones land on the human's rig, for parts with reference weights
+    let bone_map = input
+        .reference_weights
+        .as_ref()
+        .filter(|_| {
+            input
+                .parts
                 .iter()
-                .filter_map(|h| morph_target_assets.get(h).cloned())
-                .collect();
+                .any(|p| p.weights.is_some() && p.reference_weights)
+        })
+        .map(|reference| reference.bone_map(&input.skinning_weights));
 
-            // Extract for task
-            let input = HumanProcessingInput {
-                base_vertices: base_mesh.vertices.clone(),
-                base_vertex_groups: base_mesh.vertex_groups.clone(),
-                morphs: assets
-                    .morphs
-                    .iter()
-                    .filter_map(|(h, w)| morph_target_assets.get(h).map(|m| (m.clone(), *w)))
-                    .collect(),
-                rig_bones: rig_bones_assets.get(&assets.rig_bones).unwrap().clone(),
-                skinning_weights: skinning_weights_assets
-                    .get(&assets.rig_weights)
-                    .unwrap()
-                    .clone(),
-                skin_material: assets.skin_material.clone(),
-                skin_proxy,
-                clothing_offset: assets.clothing_offset,
-                parts,
-                #[cfg(feature = "arkit")]
-                arkit_targets,
-            };
+    // inner clothing layers first
+    let mut input_parts = input.parts;
+    input_parts.sort_by_key(|s| (s.source.tag == MHTag::Clothes, s.clo.z_depth));
 
-            // Spawn async task
-            let task = AsyncComputeTaskPool::get().spawn(async move { process_human(input) });
-            commands
-                .entity(e)
-                .remove::<HumanAssets>()
-                .insert(HumanProcessingTask(task));
+    let total = input_parts.len();
+    let mut parts = Vec::with_capacity(total);
+    for s in input_parts {
+        if token.is_stale() {
+            return None;
         }
+        set_progress(
+            &input.progress,
+            HumanBuildProgress::Fitting {
+                fitted: parts.len(),
+                total,
+            },
+        );
+        let offset = part_offset(
+            s.source.tag,
+            &s.clo,
+            &input.clothing_layers,
+            input.clothing_offset,
+        );
+        let weights = match (s.weights, &bone_map) {
+            (Some(weights), Some(bone_map)) if s.reference_weights => {
+                Some(weights.remap_bones(bone_map))
+            }
+            (weights, _) => weights,
+        };
+        parts.push(MHItemResult {
+            source: s.source,
+            mesh: bake_part(
+                &s.clo,
+                &s.base,
+                &baked,
+                &input.skinning_weights,
+                weights.as_ref(),
+                &input.influences,
+                offset,
+            ),
+            mat: s.mat,
+            depth_bias: part_depth_bias(s.source.tag, &s.clo, &input.clothing_layers),
+        });
     }
+
+    set_progress(&input.progress, HumanBuildProgress::Spawning);
+
+    Some(HumanProcessingOutput {
+        generation: token.generation(),
+        body,
+        parts,
+        clothing_offset: input.clothing_offset,
+        deleted_vertices: input.deleted_vertices,
+        stale_parts: input.stale_parts,
+    })
 }
 
-fn process_human(input: HumanProcessingInput) -> HumanProcessingOutput {
-    let mut morphed_vertices = input.base_vertices.clone();
+/// Morph the base mesh, build the skeleton and fit the skin proxy, hiding skin under `deleted_vertices`
+/// Returns None if the build went stale before finishing
+fn build_body(
+    input: HumanBodyBuildInput,
+    skinning_weights: &SkinningWeights,
+    deleted_vertices: &[u32],
+    influences: &SkinInfluences,
+    token: &HumanBuildToken,
+    progress: &SharedProgress,
+) -> Option<HumanBodyOutput> {
+    set_progress(progress, HumanBuildProgress::Morphing);
+    let body = bake_body(
+        &input.base_vertices,
+        &input.base_vertex_groups,
+        &input.morphs,
+        &input.rig_bones,
+    );
 
-    // Apply all morphs (unified - body morphs + macro morphs)
-    for (morph_data, weight) in &input.morphs {
-        if *weight < 0.001 {
-            continue;
-        }
-        for (&mh_idx, &offset) in &morph_data.offsets {
-            let idx = mh_idx as usize;
-            if idx < morphed_vertices.len() {
-                morphed_vertices[idx] += offset * *weight;
-            }
-        }
+    if token.is_stale() {
+        return None;
     }
 
-    // Build skeleton
-    let skeleton = input
-        .rig_bones
-        .build_skeleton(&morphed_vertices, &input.base_vertex_groups);
-
-    let mut parts = input
-        .parts
-        .into_iter()
-        .map(|s| MHItemResult {
-            tag: s.tag,
-            mesh: {
-                let mesh = apply_mhclo_fitting(
-                    &s.base.mesh,
-                    &s.clo,
-                    &s.base.mhid_lookup,
-                    &morphed_vertices,
-                    match s.tag {
-                        MHTag::Clothes => input.clothing_offset,
-                        _ => 0.0,
-                    },
-                );
-                apply_skinning_weights_via_mhclo(
-                    mesh,
-                    &s.clo,
-                    &s.base.mhid_lookup,
-                    &skeleton,
-                    &input.skinning_weights,
-                )
-            },
-            mat: s.mat,
-        })
-        .collect::<Vec<_>>();
-
-    // Skin mesh via proxy
-    let (proxy_asset, proxy_obj) = &input.skin_proxy;
-    let mut skin_mesh = apply_proxy_fitting(
-        &proxy_obj.mesh,
-        proxy_asset,
-        &morphed_vertices,
-        &proxy_obj.vertices,
-    );
-    skin_mesh = apply_skinning_weights_to_proxy(
-        skin_mesh,
-        proxy_asset,
-        &proxy_obj.mhid_lookup,
-        &skeleton,
-        &input.skinning_weights,
-    );
-    parts.push(MHItemResult {
-        tag: MHTag::Skin,
-        mesh: skin_mesh,
-        mat: input.skin_material.clone(),
+    let skin = input.skin.map(|(skin, material)| {
+        set_progress(progress, HumanBuildProgress::Skinning);
+        let mut skin = bake_skin(&skin, &body, skinning_weights, influences);
+        skin.hide_vertices(deleted_vertices);
+        (skin, material)
     });
 
-    // Calculate human height from morphed vertices
-    let (min_y, max_y) = morphed_vertices
-        .iter()
-        .fold((f32::MAX, f32::MIN), |(min, max), v| {
-            (min.min(v.y), max.max(v.y))
-        });
-    let height = max_y - min_y;
+    Some(HumanBodyOutput {
+        source: input.source,
+        body,
+        skin,
+    })
+}
 
-    #[cfg(feature = "arkit")]
-    let arkit_morphs: Vec<Vec<Vec3>> = input
-        .arkit_targets
-        .iter()
-        .map(|morph_data| {
-            proxy_obj
-                .mhid_lookup
-                .iter()
-                .map(|&obj_idx| {
-                    let binding = &proxy_asset.bindings[obj_idx as usize];
-                    let d0 = morph_data
-                        .offsets
-                        .get(&binding.triangle[0])
-                        .copied()
-                        .unwrap_or(Vec3::ZERO);
-                    let d1 = morph_data
-                        .offsets
-                        .get(&binding.triangle[1])
-                        .copied()
-                        .unwrap_or(Vec3::ZERO);
-                    let d2 = morph_data
-                        .offsets
-                        .get(&binding.triangle[2])
-                        .copied()
-                        .unwrap_or(Vec3::ZERO);
-                    // Barycentric interpolation of offsets
-                    d0 * binding.weights[0] + d1 * binding.weights[1] + d2 * binding.weights[2]
-                })
-                .collect()
-        })
-        .collect();
+/// Capsule collider sized to character: (radius, length, offset_y)
+#[cfg(feature = "avian3d")]
+fn collider_capsule(cache: &HumanBuildCache, floor_offset: f32) -> (f32, f32, f32) {
+    let radius = cache.radius.min(cache.height / 2.0);
+    let length = (cache.height - radius * 2.0).max(0.1);
+    let offset_y = cache.min_y - floor_offset + radius + length / 2.0;
+    (radius, length, offset_y)
+}
 
-    HumanProcessingOutput {
-        skeleton,
-        parts,
-        height,
-        min_y,
-        #[cfg(feature = "arkit")]
-        arkit_morphs,
+/// Body size the collider was last built for, rebuilds that keep it (eg skin material swaps) leave the collider alone
+#[cfg(feature = "avian3d")]
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+struct HumanColliderSize {
+    height: f32,
+    min_y: f32,
+    radius: f32,
+}
+
+#[cfg(feature = "avian3d")]
+impl From<&HumanBuildCache> for HumanColliderSize {
+    fn from(cache: &HumanBuildCache) -> Self {
+        Self {
+            height: cache.height,
+            min_y: cache.min_y,
+            radius: cache.radius,
+        }
     }
 }
 
+/// Set up rigid body and collider after the body changes size or when physics settings change
+#[cfg(feature = "avian3d")]
+fn update_human_physics(
+    mut commands: Commands,
+    query: Query<
+        (
+            Entity,
+            Option<Ref<HumanPhysics>>,
+            Ref<FloorOffset>,
+            &HumanBuildCache,
+            Option<&HumanColliderSize>,
+            Option<&Children>,
+            Has<RigidBody>,
+            Has<HumanRigidBody>,
+        ),
+        Or<(
+            Changed<HumanPhysics>,
+            Changed<FloorOffset>,
+            Changed<HumanBuildCache>,
+        )>,
+    >,
+    tags: Query<&MHTag>,
+) {
+    for (entity, physics, floor_offset, cache, size, children_maybe, has_body, owns_body) in
+        query.iter()
+    {
+        let new_size = HumanColliderSize::from(cache);
+        let settings_changed =
+            floor_offset.is_changed() || physics.as_ref().is_some_and(|p| p.is_changed());
+        if !settings_changed && size == Some(&new_size) {
+            continue;
+        }
+        commands.entity(entity).insert(new_size);
+
+        for e in children_maybe.into_iter().flatten() {
+            if let Ok(MHTag::Collider) = tags.get(*e) {
+                commands.entity(*e).despawn();
+            }
+        }
+
+        let (collider, offset_y) = match physics.as_deref().unwrap_or(&HumanPhysics::Capsule) {
+            HumanPhysics::None => {
+                if owns_body {
+                    commands
+                        .entity(entity)
+                        .remove::<(RigidBody, LockedAxes, HumanRigidBody)>();
+                }
+                continue;
+            }
+            HumanPhysics::Capsule => {
+                let (radius, length, offset_y) = collider_capsule(cache, floor_offset.0);
+                (Collider::capsule(radius, length), offset_y)
+            }
+            HumanPhysics::Custom(collider) => (
+                collider.clone(),
+                cache.min_y - floor_offset.0 + cache.height / 2.0,
+            ),
+        };
+
+        // keep a rigid body the user added
+        if !has_body {
+            commands.entity(entity).insert((
+                RigidBody::Dynamic,
+                LockedAxes::ROTATION_LOCKED,
+                HumanRigidBody,
+            ));
+        }
+        commands.spawn((
+            ChildOf(entity),
+            Name::new("Collider"),
+            Transform::from_translation(Vec3::Y * offset_y),
+            collider,
+            MHTag::Collider,
+        ));
+    }
+}
+
+/// Render asset stores, missing in headless mode
+#[derive(SystemParam)]
+struct HumanRenderAssets<'w> {
+    inverse_bindposes: ResMut<'w, Assets<SkinnedMeshInverseBindposes>>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    images: ResMut<'w, Assets<Image>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+}
+
 /// Update human and trigger HumanGenerate
 #[allow(unused_mut, unused_variables)]
 fn update_human(
@@ -573,164 +1164,571 @@ fn update_human(
         Entity,
         Option<&Children>,
         &mut HumanProcessingTask,
-        &FloorOffset,
+        &HumanGeneration,
+        &mut HumanBuildProgress,
+        Option<&HumanBuildCache>,
+        Option<&Mesh3d>,
     )>,
-    mut inverse_bindpose_assets: ResMut<Assets<SkinnedMeshInverseBindposes>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut images: ResMut<Assets<Image>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut render: Option<HumanRenderAssets>,
+    mh_entities: Query<(&MHTag, Option<&Name>, Option<&Children>)>,
 ) {
-    for (entity, children_maybe, mut task, floor_offset) in query.iter_mut() {
-        if let Some(HumanProcessingOutput {
-            skeleton,
+    for (entity, children_maybe, mut task, generation, mut progress, cache, skin_mesh) in
+        query.iter_mut()
+    {
+        let Some(output) = future::block_on(future::poll_once(&mut task.task)) else {
+            // stale builds keep quiet, the pending build reports once it starts
+            if !task.token.is_stale()
+                && let Ok(p) = task.progress.lock()
+            {
+                progress.set_if_neq(*p);
+            }
+            continue;
+        };
+
+        commands.entity(entity).remove::<HumanProcessingTask>(); // cleanup task
+
+        // Newer build requested while this one ran, drop it
+        let Some(HumanProcessingOutput {
+            generation: _,
+            body,
             parts,
-            height,
-            min_y,
-            #[cfg(feature = "arkit")]
-            arkit_morphs,
-        }) = future::block_on(future::poll_once(&mut task.0))
+            clothing_offset,
+            deleted_vertices,
+            stale_parts,
+        }) = output.filter(|o| o.generation == generation.current())
+        else {
+            debug!("Dropping stale build for {}", entity);
+            continue;
+        };
+
+        let rebuilt = body.is_some();
+        let mut cache = match (body, cache) {
+            (Some(body), _) => spawn_body(
+                &mut commands,
+                entity,
+                children_maybe,
+                &mh_entities,
+                body,
+                clothing_offset,
+                render.as_mut(),
+            ),
+            (None, Some(cache)) => cache.clone(),
+            (None, None) => {
+                // cache removed while building, start over
+                commands.entity(entity).insert(HumanDirty);
+                continue;
+            }
+        };
+
+        // remove replaced parts
+        for e in &stale_parts {
+            commands.entity(*e).try_despawn();
+        }
+        cache.parts.retain(|(_, e)| !stale_parts.contains(e));
+        cache.clothing_offset = clothing_offset;
+
+        // reused skin, re-mask it for the new clothing
+        if !rebuilt
+            && cache.deleted_vertices != deleted_vertices
+            && let Some(render) = render.as_mut()
+            && let Some(skin_mesh) = skin_mesh
+            && let Some(mesh) = render.meshes.get_mut(&skin_mesh.0)
         {
-            commands
-                .entity(entity)
-                .remove::<HumanProcessingTask>() // cleanup task
-                .insert(AnimationPlayer::default());
-
-            // remove all children
-            if let Some(children) = children_maybe {
-                for e in children.iter() {
-                    commands.entity(e).despawn();
+            mesh.insert_indices(Indices::U32(
+                cache.skin_mask.visible_triangles(&deleted_vertices),
+            ));
+        }
+        cache.deleted_vertices = deleted_vertices;
+
+        // parts, never built in headless mode
+        for a in parts.into_iter() {
+            let Some(render) = render.as_mut() else {
+                break;
+            };
+            // Add clearcoat for glossy wet eye look
+            #[cfg(feature = "glossy_eyes")]
+            if a.source.tag == MHTag::Eyes {
+                if let Some(mat) = render.materials.get_mut(&a.mat) {
+                    mat.clearcoat = 1.0;
+                    mat.clearcoat_perceptual_roughness = 0.1;
                 }
             }
+            // outer clothing layers win depth ties, biased on a copy so the shared material stays as is
+            let mat = match render
+                .materials
+                .get(&a.mat)
+                .filter(|_| a.depth_bias != 0.0)
+                .cloned()
+            {
+                Some(shared) => render.materials.add(StandardMaterial {
+                    depth_bias: a.depth_bias,
+                    ..shared
+                }),
+                None => a.mat,
+            };
+            let part = commands
+                .spawn((
+                    ChildOf(entity),
+                    Name::new(format!("{}", a.source.tag)),
+                    Mesh3d(render.meshes.add(a.mesh)),
+                    MeshMaterial3d(mat),
+                    cache.skinned_mesh.clone(),
+                    a.source.tag,
+                ))
+                .id();
+            cache.parts.push((a.source, part));
+        }
 
-            let mut bone_entities = Vec::with_capacity(skeleton.bones.len());
+        commands.entity(entity).insert(cache);
+        *progress = HumanBuildProgress::Complete;
 
-            // Spawn all bones
-            for (bone_idx, bone) in skeleton.bones.iter().enumerate() {
-                // Build hierarchical name path for AnimationTarget
-                // Path: bone -> ... -> root
-                let mut path = vec![Name::new(bone.name.clone())];
-                let mut current_idx = bone_idx;
+        // Notify character complete
+        commands.trigger(HumanComplete { entity });
+    }
+}
 
-                while let Some(parent_idx) = skeleton.hierarchy[current_idx] {
-                    path.push(Name::new(skeleton.bones[parent_idx].name.clone()));
-                    current_idx = parent_idx;
-                }
+/// Replace MakeHuman children with a freshly built body, returns the new build cache
+///
+/// Bones are matched by name and updated in place, children not tagged with [`MHTag`] are left alone
+#[allow(unused_mut, unused_variables, clippy::too_many_arguments)]
+fn spawn_body(
+    commands: &mut Commands,
+    entity: Entity,
+    children_maybe: Option<&Children>,
+    mh_entities: &Query<(&MHTag, Option<&Name>, Option<&Children>)>,
+    body: HumanBodyOutput,
+    clothing_offset: f32,
+    mut render: Option<&mut HumanRenderAssets>,
+) -> HumanBuildCache {
+    let HumanBodyOutput {
+        source,
+        body:
+            BakedBody {
+                morphed_vertices,
+                skeleton,
+                height,
+                min_y,
+                radius,
+                measurements,
+            },
+        skin,
+    } = body;
 
-                let bone_entity = commands
-                    .spawn((
-                        Name::new(bone.name.clone()),
-                        skeleton.bind_pose[bone_idx],
-                        GlobalTransform::default(),
-                        // AnimationTarget {
-                        //     id: AnimationTargetId::from_names(path.iter().rev()),
-                        //     player: entity,
-                        // },
-                        AnimationTargetId::from_names(path.iter().rev()),
-                        AnimatedBy(entity),
-                        Visibility::default(),
-                    ))
-                    .id();
-                bone_entities.push(bone_entity);
-            }
+    // keep a running animation going
+    commands
+        .entity(entity)
+        .insert_if_new(AnimationPlayer::default())
+        .insert(measurements);
 
-            // Wire up parent-child hierarchy
-            for (bone_idx, &parent_idx_opt) in skeleton.hierarchy.iter().enumerate() {
-                let bone = bone_entities[bone_idx];
-                if let Some(parent_idx) = parent_idx_opt {
-                    commands
-                        .entity(bone_entities[parent_idx])
-                        .add_children(&[bone]);
-                } else {
-                    // Root bones attach to parent entity
-                    commands.entity(entity).add_children(&[bone]);
+    // Remove previous parts, collect bones for reuse
+    let mut old_bones: HashMap<String, Entity> = HashMap::new();
+    for e in children_maybe.into_iter().flatten() {
+        match mh_entities.get(*e) {
+            Ok((MHTag::Armature, _, _)) => {
+                let mut stack = vec![*e];
+                while let Some(b) = stack.pop() {
+                    if let Ok((MHTag::Armature, Some(name), children)) = mh_entities.get(b) {
+                        old_bones.insert(name.to_string(), b);
+                        stack.extend(children.into_iter().flatten());
+                    }
                 }
             }
+            // replaced by update_human_physics
+            Ok((MHTag::Collider, _, _)) => {}
+            Ok(_) => {
+                commands.entity(*e).despawn();
+            }
+            // not ours
+            Err(_) => {}
+        }
+    }
 
-            // Create SkinnedMesh component - shared by body and all parts
-            let inverse_bindposes = inverse_bindpose_assets.add(skeleton.inverse_bind_matrices);
-            let skinned_mesh = SkinnedMesh {
-                inverse_bindposes,
-                joints: bone_entities.clone(),
-            };
+    let mut bone_entities = Vec::with_capacity(skeleton.bones.len());
+
+    // Spawn all bones
+    for (bone_idx, bone) in skeleton.bones.iter().enumerate() {
+        // Build hierarchical name path for AnimationTarget
+        // Path: bone -> ... -> root
+        let mut path = vec![Name::new(bone.name.clone())];
+        let mut current_idx = bone_idx;
 
-            // Capsule collider sized to character
-            let radius = 0.25;
-            let length = (height - radius * 2.0).max(0.1);
-            let offset_y = min_y - floor_offset.0 + radius + length / 2.0;
+        while let Some(parent_idx) = skeleton.hierarchy[current_idx] {
+            path.push(Name::new(skeleton.bones[parent_idx].name.clone()));
+            current_idx = parent_idx;
+        }
+
+        let target = (
+            skeleton.bind_pose[bone_idx],
+            // AnimationTarget {
+            //     id: AnimationTargetId::from_names(path.iter().rev()),
+            //     player: entity,
+            // },
+            AnimationTargetId::from_names(path.iter().rev()),
+            AnimatedBy(entity),
+        );
+        let bone_entity = match old_bones.remove(&bone.name) {
+            Some(e) => commands.entity(e).insert(target).id(),
+            None => commands
+                .spawn((
+                    Name::new(bone.name.clone()),
+                    MHTag::Armature,
+                    target,
+                    GlobalTransform::default(),
+                    Visibility::default(),
+                ))
+                .id(),
+        };
+        bone_entities.push(bone_entity);
+    }
 
-            // Body mesh on main entity + faceshape deformation data
+    // Wire up parent-child hierarchy
+    for (bone_idx, &parent_idx_opt) in skeleton.hierarchy.iter().enumerate() {
+        let bone = bone_entities[bone_idx];
+        if let Some(parent_idx) = parent_idx_opt {
             commands
-                .entity(entity)
-                .insert(RigidBody::Dynamic)
-                .insert(LockedAxes::ROTATION_LOCKED)
-                .with_child((
-                    Name::new("Collider"),
-                    Transform::from_translation(Vec3::Y * offset_y),
-                    Collider::capsule(radius, length),
-                    MHTag::Collider,
-                ));
+                .entity(bone_entities[parent_idx])
+                .add_children(&[bone]);
+        } else {
+            // Root bones attach to parent entity
+            commands.entity(entity).add_children(&[bone]);
+        }
+    }
 
-            // parts
-            for a in parts.into_iter() {
-                match a.tag {
-                    MHTag::Skin => {
-                        let mut mesh = a.mesh;
-
-                        #[cfg(feature = "arkit")]
-                        {
-                            let vertex_count = mesh.count_vertices();
-                            let targets_iter = arkit_morphs.iter().map(|mesh_offsets| {
-                                mesh_offsets.iter().map(|&offset| {
-                                    MorphAttributes::new(offset, Vec3::ZERO, Vec3::ZERO)
-                                })
-                            });
-
-                            if let Ok(morph_image) = MorphTargetImage::new(
-                                targets_iter,
-                                vertex_count,
-                                RenderAssetUsages::default(),
-                            ) {
-                                let morph_handle = images.add(morph_image.0);
-                                mesh.set_morph_targets(morph_handle);
-                                if let Ok(weights) =
-                                    MeshMorphWeights::new(vec![0.0; arkit_morphs.len()])
-                                {
-                                    commands.entity(entity).insert(weights);
-                                } else {
-                                    warn!("Failed to create MeshMorphWeights for ARKit targets");
-                                }
-                            }
-                        }
-
-                        commands.entity(entity).insert((
-                            Mesh3d(meshes.add(mesh)),
-                            MeshMaterial3d(a.mat),
-                            skinned_mesh.clone(),
-                        ));
-                    }
-                    _ => {
-                        // Add clearcoat for glossy wet eye look
-                        #[cfg(feature = "glossy_eyes")]
-                        if a.tag == MHTag::Eyes {
-                            if let Some(mat) = materials.get_mut(&a.mat) {
-                                mat.clearcoat = 1.0;
-                                mat.clearcoat_perceptual_roughness = 0.1;
-                            }
-                        }
-                        commands.spawn((
-                            ChildOf(entity),
-                            Name::new(format!("{}", a.tag)),
-                            Mesh3d(meshes.add(a.mesh)),
-                            MeshMaterial3d(a.mat),
-                            skinned_mesh.clone(),
-                            a.tag,
-                        ));
-                    }
-                };
+    // Bones missing from the new rig, move anything attached to them onto the human first
+    for &bone in old_bones.values() {
+        if let Ok((_, _, Some(children))) = mh_entities.get(bone) {
+            for &c in children {
+                if !mh_entities.contains(c) {
+                    commands.entity(entity).add_child(c);
+                }
             }
+        }
+    }
+    for &bone in old_bones.values() {
+        commands.entity(bone).try_despawn();
+    }
+
+    // Create SkinnedMesh component - shared by body and all parts
+    // headless keeps the joints only
+    let inverse_bindposes = render
+        .as_mut()
+        .map(|r| {
+            r.inverse_bindposes
+                .add(skeleton.inverse_bind_matrices.clone())
+        })
+        .unwrap_or_default();
+    let skinned_mesh = SkinnedMesh {
+        inverse_bindposes,
+        joints: bone_entities.clone(),
+    };
+
+    let skin_mask = skin
+        .as_ref()
+        .map(|(s, _)| s.mask.clone())
+        .unwrap_or_default();
+    #[cfg(feature = "arkit")]
+    let arkit_morphs = skin
+        .as_ref()
+        .map(|(s, _)| s.arkit_morphs.clone())
+        .unwrap_or_default();
+    if let Some((skin, material)) = skin
+        && let Some(render) = render
+    {
+        spawn_skin(commands, entity, skin, material, &skinned_mesh, render);
+    }
+
+    HumanBuildCache {
+        body: source,
+        clothing_offset,
+        morphed_vertices,
+        skeleton,
+        skinned_mesh,
+        height,
+        min_y,
+        radius,
+        measurements,
+        skin_mask,
+        // set by the caller along with clothing_offset
+        deleted_vertices: vec![],
+        #[cfg(feature = "arkit")]
+        arkit_morphs,
+        parts: vec![],
+    }
+}
 
-            // Notify character complete
-            commands.trigger(HumanComplete { entity });
+/// Skin mesh and ARKit morph targets on the human entity
+fn spawn_skin(
+    commands: &mut Commands,
+    entity: Entity,
+    skin: BakedSkin,
+    material: Handle<StandardMaterial>,
+    skinned_mesh: &SkinnedMesh,
+    render: &mut HumanRenderAssets,
+) {
+    let mut mesh = skin.mesh;
+
+    #[cfg(feature = "arkit")]
+    {
+        let arkit_morphs = skin.arkit_morphs;
+        let vertex_count = mesh.count_vertices();
+        let targets_iter = arkit_morphs.iter().map(|mesh_offsets| {
+            mesh_offsets
+                .iter()
+                .map(|&offset| MorphAttributes::new(offset, Vec3::ZERO, Vec3::ZERO))
+        });
+
+        if let Ok(morph_image) =
+            MorphTargetImage::new(targets_iter, vertex_count, RenderAssetUsages::default())
+        {
+            let morph_handle = render.images.add(morph_image.0);
+            mesh.set_morph_targets(morph_handle);
+            if let Ok(weights) = MeshMorphWeights::new(vec![0.0; arkit_morphs.len()]) {
+                commands.entity(entity).insert(weights);
+            } else {
+                warn!("Failed to create MeshMorphWeights for ARKit targets");
+            }
         }
     }
+
+    commands.entity(entity).insert((
+        Mesh3d(render.meshes.add(mesh)),
+        MeshMaterial3d(material),
+        skinned_mesh.clone(),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mhm_preset() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<HumanDescriptor>()
+            .init_asset_loader::<HumanDescriptorLoader>()
+            .add_systems(Update, apply_human_presets);
+
+        let preset = app
+            .world()
+            .resource::<AssetServer>()
+            .load::<HumanDescriptor>("presets/bob.mhm");
+        let entity = app.world_mut().spawn(HumanPreset(preset)).id();
+
+        for _ in 0..100 {
+            app.update();
+            if app.world().entity(entity).contains::<Human>() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        let human = app.world().entity(entity);
+        assert_eq!(human.get::<Name>().map(Name::as_str), Some("Bob"));
+        assert_eq!(human.get::<Rig>(), Some(&Rig::Mixamo));
+        assert_eq!(human.get::<SkinMesh>(), Some(&SkinMesh::MaleGeneric));
+        assert_eq!(
+            human.get::<Outfit>().map(|o| o.0.clone()),
+            Some(vec![
+                Clothing::ToigoMaleSuit3,
+                Clothing::ToigoAnkleBootsMale
+            ])
+        );
+        assert_eq!(human.get::<MacroSliders>().map(|s| s.muscle), Some(0.6));
+    }
+
+    #[test]
+    fn test_headless_plugin() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            MakeHumanPlugin {
+                cache_dir: None,
+                headless: true,
+            },
+        ));
+        app.update();
+
+        let world = app.world();
+        assert!(world.contains_resource::<MHHeadless>());
+        assert!(world.contains_resource::<AssetServer>());
+        assert!(world.contains_resource::<Assets<HumanDescriptor>>());
+        // no render stores, update_human must not need them
+        assert!(!world.contains_resource::<Assets<StandardMaterial>>());
+        assert!(!world.contains_resource::<Assets<Mesh>>());
+        assert_eq!(
+            world.resource::<State<MHState>>().get(),
+            &MHState::LoadingAssets
+        );
+    }
+
+    #[test]
+    fn test_failed_load_names_path() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<MhcloAsset>()
+            .init_asset_loader::<MhcloLoader>();
+
+        // added assets never fail
+        let added = app
+            .world_mut()
+            .resource_mut::<Assets<MhcloAsset>>()
+            .add(MhcloAsset::default())
+            .untyped();
+        let missing = app
+            .world()
+            .resource::<AssetServer>()
+            .load::<MhcloAsset>("clothes/missing/missing.mhclo")
+            .untyped();
+        let handles = [added, missing];
+
+        let mut error = None;
+        for _ in 0..100 {
+            app.update();
+            error = failed_load(app.world().resource::<AssetServer>(), &handles);
+            if error.is_some() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        let Some(HumanError::AssetLoad { path, .. }) = error else {
+            panic!("expected an asset load error, got {error:?}");
+        };
+        assert!(path.ends_with("missing.mhclo"), "{path}");
+        assert!(failed_load(app.world().resource::<AssetServer>(), &handles[..1]).is_none());
+    }
+
+    /// Skeleton of `names`, each bone the child of the previous one
+    fn chain_skeleton(names: &[&str]) -> Skeleton {
+        let bones = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Bone {
+                name: name.to_string(),
+                head: Vec3::Y * i as f32,
+                tail: Vec3::Y * (i + 1) as f32,
+                roll: 0.0,
+            })
+            .collect();
+        let hierarchy = (0..names.len()).map(|i| i.checked_sub(1)).collect();
+        Skeleton::new(bones, hierarchy)
+    }
+
+    fn rebuild_body(
+        In((entity, skeleton)): In<(Entity, Skeleton)>,
+        mut commands: Commands,
+        children: Query<&Children>,
+        mh_entities: Query<(&MHTag, Option<&Name>, Option<&Children>)>,
+    ) -> HumanBuildCache {
+        let body = HumanBodyOutput {
+            source: HumanBodySource {
+                rig: Rig::default(),
+                skin_mesh: SkinMesh::default(),
+                skin_material: SkinMaterial::default(),
+                morphs: Morphs::default(),
+                macro_sliders: None,
+                influences: SkinInfluences::default(),
+            },
+            body: BakedBody {
+                morphed_vertices: vec![],
+                skeleton,
+                height: 1.8,
+                min_y: 0.0,
+                radius: 0.2,
+                measurements: HumanMeasurements::default(),
+            },
+            skin: None,
+        };
+        let children = children.get(entity).ok();
+        spawn_body(
+            &mut commands,
+            entity,
+            children,
+            &mh_entities,
+            body,
+            0.0,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_rebuild_keeps_bones_and_children() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        let human = world.spawn_empty().id();
+        let skeleton = chain_skeleton(&["root", "spine", "hand"]);
+        world
+            .run_system_once_with(rebuild_body, (human, skeleton))
+            .unwrap();
+
+        let mut bone = |name: &str| {
+            world
+                .query::<(Entity, &Name)>()
+                .iter(&world)
+                .find(|(_, n)| n.as_str() == name)
+                .map(|(e, _)| e)
+        };
+        let (root, spine, hand) = (bone("root"), bone("spine"), bone("hand"));
+        let (root, spine, hand) = (root.unwrap(), spine.unwrap(), hand.unwrap());
+
+        let sword = world.spawn(ChildOf(hand)).id();
+        let lamp = world.spawn(ChildOf(spine)).id();
+        let hat = world.spawn(ChildOf(human)).id();
+        let hair = world.spawn((MHTag::Hair, ChildOf(human))).id();
+
+        // new rig without the hand
+        let skeleton = chain_skeleton(&["root", "spine"]);
+        let cache = world
+            .run_system_once_with(rebuild_body, (human, skeleton))
+            .unwrap();
+
+        assert_eq!(cache.skinned_mesh.joints, vec![root, spine]);
+        assert!(world.get_entity(hand).is_err());
+        assert!(world.get_entity(hair).is_err());
+        let parent = |e: Entity| world.get::<ChildOf>(e).map(ChildOf::parent);
+        assert_eq!(parent(root), Some(human));
+        assert_eq!(parent(spine), Some(root));
+        assert_eq!(parent(lamp), Some(spine));
+        assert_eq!(parent(hat), Some(human));
+        // children of removed bones move to the human
+        assert_eq!(parent(sword), Some(human));
+    }
+
+    #[derive(Resource, Default)]
+    struct Failed(Vec<Entity>);
+
+    #[test]
+    fn test_missing_preset_fails() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<HumanDescriptor>()
+            .init_asset_loader::<HumanDescriptorLoader>()
+            .init_resource::<Failed>()
+            .add_observer(|failed: On<HumanFailed>, mut seen: ResMut<Failed>| {
+                seen.0.push(failed.entity);
+            })
+            .add_systems(Update, apply_human_presets);
+
+        let preset = app
+            .world()
+            .resource::<AssetServer>()
+            .load::<HumanDescriptor>("presets/missing.human.ron");
+        let entity = app.world_mut().spawn(HumanPreset(preset)).id();
+
+        for _ in 0..100 {
+            app.update();
+            if !app.world().resource::<Failed>().0.is_empty() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        assert_eq!(app.world().resource::<Failed>().0, vec![entity]);
+        assert_eq!(
+            app.world().get::<HumanBuildProgress>(entity),
+            Some(&HumanBuildProgress::Failed)
+        );
+    }
 }
@@ -0,0 +1,151 @@
+//! .human.ron / .human.json preset - a whole character in one file
+//!
+//! Format: serde of [`HumanDescriptor`], missing fields use their defaults
+//!   (
+//!       rig: Mixamo,
+//!       skin_mesh: MaleGeneric,
+//!       skin_material: YoungCaucasianMale,
+//!       hair: Some(CulturalibreHair02),
+//!       outfit: [ToigoMaleSuit3, ToigoAnkleBootsMale],
+//!       macro_sliders: Some((gender: 1.0, muscle: 0.7, african: 0.0, asian: 0.0, caucasian: 1.0)),
+//!   )
+//!
+//! MakeHuman .mhm files load as presets too, see [`MhmAsset::to_descriptor`]
+
+use crate::{
+    assets::*,
+    components::*,
+    loaders::{MhmAsset, MhmLoaderError},
+};
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Everything needed to build a human, see [`HumanPreset`]
+#[derive(Asset, TypePath, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HumanDescriptor {
+    /// Inserted as the entity's [`Name`]
+    pub name: Option<String>,
+    pub rig: Rig,
+    pub skin_mesh: SkinMesh,
+    pub skin_material: SkinMaterial,
+    pub eyes: Eyes,
+    pub hair: Option<Hair>,
+    pub eyebrows: Eyebrows,
+    pub eyelashes: Eyelashes,
+    pub teeth: Teeth,
+    pub tongue: Tongue,
+    pub outfit: Vec<Clothing>,
+    pub morphs: Vec<Morph>,
+    pub macro_sliders: Option<MacroSliders>,
+    pub clothing_offset: f32,
+    pub floor_offset: f32,
+}
+
+impl HumanDescriptor {
+    /// Insert as [`Human`] components, replacing any existing ones
+    pub fn insert(&self, entity: &mut EntityCommands) {
+        entity.insert((
+            Human,
+            self.rig,
+            self.skin_mesh,
+            self.skin_material,
+            self.eyes,
+            self.eyebrows,
+            self.eyelashes,
+            self.teeth,
+            self.tongue,
+            Outfit(self.outfit.clone()),
+            Morphs(self.morphs.clone()),
+            ClothingOffset(self.clothing_offset),
+            FloorOffset(self.floor_offset),
+        ));
+        match self.hair {
+            Some(hair) => entity.insert(hair),
+            None => entity.remove::<Hair>(),
+        };
+        match self.macro_sliders {
+            Some(sliders) => entity.insert(sliders),
+            None => entity.remove::<MacroSliders>(),
+        };
+        if let Some(name) = &self.name {
+            entity.insert(Name::new(name.clone()));
+        }
+    }
+}
+
+impl HumanDescriptor {
+    /// [`Self::macro_sliders`], or gender, age and ethnicity read back from ethnic macro morphs
+    pub fn sliders(&self) -> MacroSliders {
+        self.macro_sliders
+            .unwrap_or_else(|| MacroSliders::from_ethnic_morphs(&self.morphs))
+    }
+}
+
+impl From<&HumanQueryItem<'_, '_>> for HumanDescriptor {
+    fn from(human: &HumanQueryItem<'_, '_>) -> Self {
+        Self {
+            name: human.name.map(|n| n.to_string()),
+            rig: *human.rig,
+            skin_mesh: *human.skin_mesh,
+            skin_material: *human.skin_material,
+            eyes: *human.eyes,
+            hair: human.hair.copied(),
+            eyebrows: *human.eyebrows,
+            eyelashes: *human.eyelashes,
+            teeth: *human.teeth,
+            tongue: *human.tongue,
+            outfit: human.clothing.0.clone(),
+            morphs: human.morphs.0.clone(),
+            macro_sliders: human.macro_sliders.copied(),
+            clothing_offset: human.clothing_offset.0,
+            floor_offset: human.floor_offset.0,
+        }
+    }
+}
+
+#[derive(Default, TypePath)]
+pub struct HumanDescriptorLoader;
+
+#[derive(Debug, Error)]
+pub enum HumanDescriptorLoaderError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("RON error: {0}")]
+    Ron(#[from] ron::de::SpannedError),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("MHM error: {0}")]
+    Mhm(#[from] MhmLoaderError),
+}
+
+impl AssetLoader for HumanDescriptorLoader {
+    type Asset = HumanDescriptor;
+    type Settings = ();
+    type Error = HumanDescriptorLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let extension = load_context.path().path().extension();
+        Ok(match extension.and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_slice(&bytes)?,
+            Some("mhm") => MhmAsset::parse(&String::from_utf8_lossy(&bytes))?.to_descriptor(),
+            _ => ron::de::from_bytes(&bytes)?,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["human.ron", "human.json", "mhm"]
+    }
+}
@@ -5,14 +5,21 @@
 //! - v0,v1,v2: base mesh triangle vertex indices
 //! - w0,w1,w2: barycentric weights
 //! - x,y,z: position offsets
+//!
+//! A "delete_verts" section lists base mesh vertices the asset covers, `a - b` is an inclusive range
+//!
+//! Other lines are `key value` metadata, `# author`/`# license`/`# homepage` comments count too.
+//! Keys without a field are kept in [`MhcloAsset::extra`]
 
 use bevy::{
-    asset::{AssetLoader, LoadContext, io::Reader},
+    asset::{AssetLoader, LoadContext, ParseAssetPathError, io::Reader},
     prelude::*,
 };
 use std::io::{BufRead, BufReader};
 use thiserror::Error;
 
+use super::SkinningWeights;
+
 #[derive(Debug, Clone)]
 pub struct VertexBinding {
     /// Base mesh triangle indices
@@ -42,12 +49,34 @@ pub struct MhcloAsset {
     pub z_scale: Option<ScaleRef>,
     /// Associated .obj mesh path (relative)
     pub obj_file: Option<String>,
-    /// Material file path
+    /// Material file path, the first `material` line
     pub material: Option<String>,
+    /// Every `material` line, the default first then its variants
+    pub materials: Vec<String>,
+    /// Vertex bone weights file path (relative), the asset's own skinning, made for [`crate::assets::REFERENCE_RIG`]
+    pub vertexboneweights_file: Option<String>,
+    /// Loaded `vertexboneweights_file`, keyed by this asset's obj vertices
+    pub vertex_bone_weights: Option<Handle<SkinningWeights>>,
     /// Asset name
     pub name: String,
+    pub uuid: Option<String>,
+    /// Lowercased tags
+    pub tags: Vec<String>,
+    pub version: Option<String>,
+    pub author: Option<String>,
+    pub license: Option<String>,
+    pub homepage: Option<String>,
+    pub description: Option<String>,
     /// Z-depth ordering
     pub z_depth: u32,
+    /// Max vertex pole count of the mesh
+    pub max_pole: Option<u32>,
+    /// Pose overrides: (pose type, file)
+    pub special_poses: Vec<(String, String)>,
+    /// Sorted base mesh vertices hidden under this asset
+    pub delete_verts: Vec<u32>,
+    /// Any other `key value` lines, in file order
+    pub extra: Vec<(String, String)>,
 }
 
 #[derive(Default, TypePath)]
@@ -59,6 +88,11 @@ pub enum MhcloLoaderError {
     Io(#[from] std::io::Error),
     #[error("Parse error: {0}")]
     Parse(String),
+    #[error("Invalid vertexboneweights_file '{file}': {source}")]
+    WeightsPath {
+        file: String,
+        source: ParseAssetPathError,
+    },
 }
 
 impl AssetLoader for MhcloLoader {
@@ -70,20 +104,82 @@ impl AssetLoader for MhcloLoader {
         &self,
         reader: &mut dyn Reader,
         _settings: &Self::Settings,
-        _load_context: &mut LoadContext<'_>,
+        load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
 
+        let mut asset = MhcloAsset::parse(&bytes)?;
+
+        // Own weights load with the asset, fitting waits for them
+        if let Some(file) = &asset.vertexboneweights_file {
+            let path = load_context.path().resolve_embed(file).map_err(|source| {
+                MhcloLoaderError::WeightsPath {
+                    file: file.clone(),
+                    source,
+                }
+            })?;
+            asset.vertex_bone_weights = Some(load_context.load(path));
+        }
+
+        Ok(asset)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["mhclo"]
+    }
+}
+
+/// Parse a delete_verts line into `out`, false if it isn't one
+fn parse_delete_verts(line: &str, out: &mut Vec<u32>) -> bool {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if !words.iter().all(|w| *w == "-" || w.parse::<u32>().is_ok()) {
+        return false;
+    }
+
+    let mut prev: Option<u32> = None;
+    let mut range = false;
+    for word in words {
+        if word == "-" {
+            range = true;
+            continue;
+        }
+        let Ok(v) = word.parse::<u32>() else {
+            continue;
+        };
+        match prev {
+            Some(start) if range => out.extend(start + 1..=v),
+            _ => out.push(v),
+        }
+        prev = Some(v);
+        range = false;
+    }
+    true
+}
+
+impl MhcloAsset {
+    /// Parse .mhclo text, `vertex_bone_weights` is left for the loader to load
+    pub fn parse(bytes: &[u8]) -> Result<Self, MhcloLoaderError> {
         let mut asset = MhcloAsset::default();
-        let buf_reader = BufReader::new(&bytes[..]);
+        let buf_reader = BufReader::new(bytes);
         let mut in_verts_section = false;
+        let mut in_delete_verts_section = false;
 
         for line_result in buf_reader.lines() {
             let line = line_result?;
             let line_trim = line.trim();
 
-            if line_trim.is_empty() || line_trim.starts_with('#') {
+            if line_trim.is_empty() {
+                continue;
+            }
+
+            // Author metadata lives in comments, "# author name"
+            if let Some(comment) = line_trim.strip_prefix('#') {
+                if let Some((key, value)) = comment.trim().split_once(char::is_whitespace)
+                    && matches!(key.trim_end_matches(':'), "author" | "license" | "homepage")
+                {
+                    asset.set_metadata(key.trim_end_matches(':'), value.trim());
+                }
                 continue;
             }
 
@@ -93,6 +189,31 @@ impl AssetLoader for MhcloLoader {
                 continue;
             }
 
+            // Check if entering delete_verts section, it follows the verts
+            if line_trim == "delete_verts" {
+                in_verts_section = false;
+                in_delete_verts_section = true;
+                continue;
+            }
+
+            // Parse delete_verts section: indices and "a - b" ranges, until the next key
+            if in_delete_verts_section {
+                if parse_delete_verts(line_trim, &mut asset.delete_verts) {
+                    continue;
+                }
+                in_delete_verts_section = false;
+            }
+
+            // The verts section ends at the next key
+            if in_verts_section
+                && line_trim
+                    .split_whitespace()
+                    .next()
+                    .is_some_and(|w| w.parse::<f32>().is_err())
+            {
+                in_verts_section = false;
+            }
+
             // Parse verts section (can be 9-value barycentric OR single indices)
             // Mixed formats possible (e.g., eyelashes02) - unify into bindings
             if in_verts_section {
@@ -155,11 +276,37 @@ impl AssetLoader for MhcloLoader {
             }
 
             // Parse key-value metadata
-            if let Some((key, value)) = line_trim.split_once(' ') {
+            if let Some((key, value)) = line_trim
+                .split_once(char::is_whitespace)
+                .map(|(k, v)| (k, v.trim()))
+            {
                 match key {
                     "name" => asset.name = value.to_string(),
                     "obj_file" => asset.obj_file = Some(value.to_string()),
-                    "material" => asset.material = Some(value.to_string()),
+                    "material" => {
+                        asset.material.get_or_insert_with(|| value.to_string());
+                        asset.materials.push(value.to_string());
+                    }
+                    "vertexboneweights_file" => {
+                        asset.vertexboneweights_file = Some(value.to_string());
+                    }
+                    "tag" => {
+                        let tag = value.to_lowercase().replace(['"', ',', '\\'], "");
+                        if !tag.is_empty() {
+                            asset.tags.push(tag);
+                        }
+                    }
+                    "uuid" | "version" | "author" | "license" | "homepage" | "description" => {
+                        asset.set_metadata(key, value);
+                    }
+                    "max_pole" => asset.max_pole = value.parse().ok(),
+                    "special_pose" => {
+                        if let Some((kind, file)) = value.split_once(char::is_whitespace) {
+                            asset
+                                .special_poses
+                                .push((kind.to_string(), file.trim().to_string()));
+                        }
+                    }
                     "z_depth" => {
                         asset.z_depth = value.parse().unwrap_or(0);
                     }
@@ -223,20 +370,44 @@ impl AssetLoader for MhcloLoader {
                             });
                         }
                     }
-                    _ => {}
+                    _ => asset.extra.push((key.to_string(), value.to_string())),
                 }
             }
         }
 
+        asset.delete_verts.sort_unstable();
+        asset.delete_verts.dedup();
+
         Ok(asset)
     }
 
-    fn extensions(&self) -> &[&str] {
-        &["mhclo"]
+    /// Set a free text metadata field by its mhclo key
+    fn set_metadata(&mut self, key: &str, value: &str) {
+        let field = match key {
+            "uuid" => &mut self.uuid,
+            "version" => &mut self.version,
+            "author" => &mut self.author,
+            "license" => &mut self.license,
+            "homepage" => &mut self.homepage,
+            "description" => &mut self.description,
+            _ => return,
+        };
+        *field = Some(value.to_string());
+    }
+
+    /// Check for a tag, case insensitive
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Value of the first `extra` line with this key
+    pub fn extra(&self, key: &str) -> Option<&str> {
+        self.extra
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
     }
-}
 
-impl MhcloAsset {
     /// Check if this uses simple vertex mapping (eyes/teeth) vs full bindings (clothing)
     pub fn has_vertex_mapping(&self) -> bool {
         !self.vertex_mapping.is_empty()
@@ -342,3 +513,54 @@ impl MhcloAsset {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_delete_verts() {
+        let mut out = vec![];
+        assert!(parse_delete_verts("10 - 13 20", &mut out));
+        assert!(parse_delete_verts("5 7", &mut out));
+        assert!(!parse_delete_verts("z_depth 50", &mut out));
+        assert_eq!(out, vec![10, 11, 12, 13, 20, 5, 7]);
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        let text = b"\
+# author Jane Doe
+# license CC0
+name shirt01
+uuid 5c7e2a10-8b1d-4d0e-9f3a-1a2b3c4d5e6f
+tag Shirt
+tag \"casual\",
+material shirt01.mhmat
+material shirt01_red.mhmat
+z_depth 50
+x_scale 5399 11998 1.4
+scale_fabric 1.2
+verts 0
+100 101 102 0.5 0.25 0.25 0.0 0.1 0.0
+delete_verts
+1 - 3
+";
+        let asset = MhcloAsset::parse(text).unwrap();
+        assert_eq!(asset.name, "shirt01");
+        assert_eq!(
+            asset.uuid.as_deref(),
+            Some("5c7e2a10-8b1d-4d0e-9f3a-1a2b3c4d5e6f")
+        );
+        assert_eq!(asset.tags, vec!["shirt", "casual"]);
+        assert!(asset.has_tag("SHIRT"));
+        assert_eq!(asset.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(asset.license.as_deref(), Some("CC0"));
+        assert_eq!(asset.material.as_deref(), Some("shirt01.mhmat"));
+        assert_eq!(asset.materials.len(), 2);
+        assert_eq!(asset.z_depth, 50);
+        assert_eq!(asset.extra("scale_fabric"), Some("1.2"));
+        assert_eq!(asset.bindings.len(), 1);
+        assert_eq!(asset.delete_verts, vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,574 @@
+//! .mhm file parser/writer - MakeHuman and MPFB2 saved characters
+//!
+//! Format: ASCII, one `key value...` per line
+//!   version v1.1.1
+//!   name Bob
+//!   modifier head/head-age-decr|incr 0.250000
+//!   modifier macrodetails/Gender 1.000000
+//!   skeleton game_engine.mhskel
+//!   skinMaterial skins/young_caucasian_male/young_caucasian_male.mhmat
+//!   proxy male_generic <uuid>
+//!   eyes HighPolyEyes <uuid>
+//!   material HighPolyEyes <uuid> eyes/materials/brown.mhmat
+//!   clothes tshirt02 <uuid>
+//!
+//! Parts are referenced by name and uuid, resolved against the generated asset enums
+
+use crate::{assets::*, components::MacroSliders, loaders::HumanDescriptor};
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    platform::collections::HashMap,
+    prelude::*,
+};
+use std::{fmt, path::Path};
+use strum::{EnumProperty, IntoEnumIterator};
+use thiserror::Error;
+
+/// Part reference, `<key> <name> <uuid>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MhmRef {
+    pub name: String,
+    pub uuid: Option<String>,
+}
+
+impl MhmRef {
+    fn parse(value: &str) -> Self {
+        let mut parts = value.split_whitespace();
+        Self {
+            name: parts.next().unwrap_or_default().to_string(),
+            uuid: parts.next().map(str::to_string),
+        }
+    }
+}
+
+impl fmt::Display for MhmRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.uuid {
+            Some(uuid) => write!(f, "{} {}", self.name, uuid),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+#[derive(Asset, TypePath, Debug, Clone, Default)]
+pub struct MhmAsset {
+    pub version: Option<String>,
+    pub name: Option<String>,
+    pub tags: Vec<String>,
+    /// Skeleton file, eg `game_engine.mhskel`
+    pub skeleton: Option<String>,
+    /// Skin mhmat relative to the MakeHuman data dir
+    pub skin_material: Option<String>,
+    pub proxy: Option<MhmRef>,
+    pub eyes: Option<MhmRef>,
+    pub eyebrows: Option<MhmRef>,
+    pub eyelashes: Option<MhmRef>,
+    pub hair: Option<MhmRef>,
+    pub teeth: Option<MhmRef>,
+    pub tongue: Option<MhmRef>,
+    pub clothes: Vec<MhmRef>,
+    /// Material overrides (`material <name> <uuid> <mhmat>`), eye color lives here
+    pub materials: Vec<(MhmRef, String)>,
+    /// Modifier names and values in file order, macros included
+    pub modifiers: Vec<(String, f32)>,
+    /// Lines we don't use (camera, subdivide, ...), written back as is
+    pub other: Vec<String>,
+}
+
+#[derive(Default, TypePath)]
+pub struct MhmLoader;
+
+#[derive(Debug, Error)]
+pub enum MhmLoaderError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Parse error: {0}")]
+    Parse(String),
+}
+
+impl AssetLoader for MhmLoader {
+    type Asset = MhmAsset;
+    type Settings = ();
+    type Error = MhmLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        MhmAsset::parse(&String::from_utf8_lossy(&bytes))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["mhm"]
+    }
+}
+
+impl MhmAsset {
+    pub fn parse(text: &str) -> Result<Self, MhmLoaderError> {
+        let mut asset = MhmAsset::default();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let value = value.trim();
+
+            match key {
+                "version" => asset.version = Some(value.to_string()),
+                "name" => asset.name = Some(value.to_string()),
+                "tags" => asset.tags = value.split_whitespace().map(str::to_string).collect(),
+                "skeleton" => asset.skeleton = Some(value.to_string()),
+                "skinMaterial" => asset.skin_material = Some(value.to_string()),
+                "proxy" => asset.proxy = Some(MhmRef::parse(value)),
+                "eyes" => asset.eyes = Some(MhmRef::parse(value)),
+                "eyebrows" => asset.eyebrows = Some(MhmRef::parse(value)),
+                "eyelashes" => asset.eyelashes = Some(MhmRef::parse(value)),
+                "hair" => asset.hair = Some(MhmRef::parse(value)),
+                "teeth" => asset.teeth = Some(MhmRef::parse(value)),
+                "tongue" => asset.tongue = Some(MhmRef::parse(value)),
+                "clothes" => asset.clothes.push(MhmRef::parse(value)),
+                "material" => {
+                    // uuid is optional, parts written without one have none
+                    let parts: Vec<&str> = value.split_whitespace().collect();
+                    match parts[..] {
+                        [name, uuid, mhmat] => asset.materials.push((
+                            MhmRef {
+                                name: name.to_string(),
+                                uuid: Some(uuid.to_string()),
+                            },
+                            mhmat.to_string(),
+                        )),
+                        [name, mhmat] => asset.materials.push((
+                            MhmRef {
+                                name: name.to_string(),
+                                uuid: None,
+                            },
+                            mhmat.to_string(),
+                        )),
+                        _ => asset.other.push(line.to_string()),
+                    }
+                }
+                "modifier" => {
+                    let (name, v) = value
+                        .rsplit_once(' ')
+                        .ok_or_else(|| MhmLoaderError::Parse(format!("modifier: {}", value)))?;
+                    let v = v
+                        .parse()
+                        .map_err(|_| MhmLoaderError::Parse(format!("modifier value: {}", value)))?;
+                    asset.modifiers.push((name.trim().to_string(), v));
+                }
+                _ => asset.other.push(line.to_string()),
+            }
+        }
+
+        Ok(asset)
+    }
+
+    /// Value of a modifier by its label, eg `Gender` for `macrodetails/Gender`
+    pub fn modifier(&self, label: &str) -> Option<f32> {
+        self.modifiers
+            .iter()
+            .find(|(name, _)| modifier_label(name) == label)
+            .map(|(_, v)| *v)
+    }
+
+    /// Resolve the character against this asset tree
+    ///
+    /// Parts are matched by uuid, then by name. Anything missing keeps its default and is logged.
+    /// Macros become [`MacroSliders`], the rest of the modifiers [`Morph`]s
+    pub fn to_descriptor(&self) -> HumanDescriptor {
+        let mut human = HumanDescriptor {
+            name: self.name.clone(),
+            ..default()
+        };
+
+        if let Some(skeleton) = &self.skeleton {
+            let stem = file_stem(skeleton);
+            match Rig::iter().find(|r| rig_name(r) == stem) {
+                Some(rig) => human.rig = rig,
+                None => warn!("mhm skeleton {} not found", skeleton),
+            }
+        }
+        if let Some(proxy) = &self.proxy {
+            resolve(proxy, "proxy", &mut human.skin_mesh);
+        }
+        if let Some(skin) = &self.skin_material {
+            let stem = file_stem(skin);
+            match SkinMaterial::iter().find(|s| file_stem(s.mhmat()) == stem) {
+                Some(s) => human.skin_material = s,
+                None => warn!("mhm skinMaterial {} not found", skin),
+            }
+        }
+        if let Some(eyes) = &self.eyes {
+            let material = self
+                .materials
+                .iter()
+                .find(|(r, _)| r.name == eyes.name || (r.uuid.is_some() && r.uuid == eyes.uuid))
+                .map(|(_, mhmat)| file_stem(mhmat));
+            match find_eyes(eyes, material) {
+                Some(e) => human.eyes = e,
+                None => warn!("mhm eyes {} not found", eyes),
+            }
+        }
+        if let Some(r) = &self.eyebrows {
+            resolve(r, "mhclo", &mut human.eyebrows);
+        }
+        if let Some(r) = &self.eyelashes {
+            resolve(r, "mhclo", &mut human.eyelashes);
+        }
+        if let Some(r) = &self.teeth {
+            resolve(r, "mhclo", &mut human.teeth);
+        }
+        if let Some(r) = &self.tongue {
+            resolve(r, "mhclo", &mut human.tongue);
+        }
+        if let Some(r) = &self.hair {
+            human.hair = find_part(r, "mhclo");
+            if human.hair.is_none() {
+                warn!("mhm hair {} not found", r);
+            }
+        }
+        for r in &self.clothes {
+            match find_part(r, "mhclo") {
+                Some(c) => human.outfit.push(c),
+                None => warn!("mhm clothes {} not found", r),
+            }
+        }
+
+        // body modifiers
+        let targets: HashMap<String, MorphTarget> = MorphTarget::iter()
+            .filter_map(|t| Some((modifier_label(&modifier_name(&t)?).to_string(), t)))
+            .collect();
+        for (name, value) in &self.modifiers {
+            if let Some(target) = targets.get(modifier_label(name)) {
+                if value.abs() > 0.0001 {
+                    human.morphs.push(Morph::new(*target, *value));
+                }
+            } else if !MACRO_MODIFIERS.contains(&modifier_label(name)) {
+                debug!("mhm modifier {} not found", name);
+            }
+        }
+
+        // macro sliders, missing ones keep makehuman's defaults
+        let defaults = MacroSliders::default();
+        let slider = |label: &str, default: f32| self.modifier(label).unwrap_or(default);
+        human.macro_sliders = Some(MacroSliders {
+            gender: slider("Gender", defaults.gender),
+            age: slider("Age", defaults.age),
+            muscle: slider("Muscle", defaults.muscle),
+            weight: slider("Weight", defaults.weight),
+            height: slider("Height", defaults.height),
+            proportions: slider("BodyProportions", defaults.proportions),
+            african: slider("African", defaults.african),
+            asian: slider("Asian", defaults.asian),
+            caucasian: slider("Caucasian", defaults.caucasian),
+            breast_size: slider("BreastSize", defaults.breast_size),
+            breast_firmness: slider("BreastFirmness", defaults.breast_firmness),
+        });
+
+        human
+    }
+
+    /// Describe a human as a .mhm, write it with `to_string()`
+    pub fn from_descriptor(human: &HumanDescriptor) -> Self {
+        let mut asset = MhmAsset {
+            version: Some("v1.1.1".to_string()),
+            name: human.name.clone(),
+            skeleton: Some(format!("{}.mhskel", rig_name(&human.rig))),
+            skin_material: Some(data_path(human.skin_material.mhmat()).to_string()),
+            proxy: Some(part_ref(&human.skin_mesh, "proxy")),
+            eyebrows: Some(part_ref(&human.eyebrows, "mhclo")),
+            eyelashes: Some(part_ref(&human.eyelashes, "mhclo")),
+            teeth: Some(part_ref(&human.teeth, "mhclo")),
+            tongue: Some(part_ref(&human.tongue, "mhclo")),
+            hair: human.hair.as_ref().map(|h| part_ref(h, "mhclo")),
+            clothes: human.outfit.iter().map(|c| part_ref(c, "mhclo")).collect(),
+            ..default()
+        };
+        let eyes = part_ref(&human.eyes, "mhclo");
+        asset
+            .materials
+            .push((eyes.clone(), data_path(human.eyes.mhmat()).to_string()));
+        asset.eyes = Some(eyes);
+
+        for morph in human.morphs.iter() {
+            asset
+                .modifiers
+                .extend(modifier_name(&morph.target).map(|name| (name, morph.value)));
+        }
+
+        let sliders = human.sliders();
+        let macros = [
+            ("macrodetails/Gender", sliders.gender),
+            ("macrodetails/Age", sliders.age),
+            ("macrodetails/African", sliders.african),
+            ("macrodetails/Asian", sliders.asian),
+            ("macrodetails/Caucasian", sliders.caucasian),
+            ("macrodetails-universal/Muscle", sliders.muscle),
+            ("macrodetails-universal/Weight", sliders.weight),
+            ("macrodetails-height/Height", sliders.height),
+            (
+                "macrodetails-proportions/BodyProportions",
+                sliders.proportions,
+            ),
+            ("breast/BreastSize", sliders.breast_size),
+            ("breast/BreastFirmness", sliders.breast_firmness),
+        ]
+        .map(|(name, value)| (name.to_string(), value));
+        asset.modifiers.splice(0..0, macros);
+
+        asset
+    }
+}
+
+impl fmt::Display for MhmAsset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# Written by bevy_make_human")?;
+        writeln!(f, "version {}", self.version.as_deref().unwrap_or("v1.1.1"))?;
+        if let Some(name) = &self.name {
+            writeln!(f, "name {}", name)?;
+        }
+        if !self.tags.is_empty() {
+            writeln!(f, "tags {}", self.tags.join(" "))?;
+        }
+        for (name, value) in &self.modifiers {
+            writeln!(f, "modifier {} {:.6}", name, value)?;
+        }
+        if let Some(skeleton) = &self.skeleton {
+            writeln!(f, "skeleton {}", skeleton)?;
+        }
+        if let Some(proxy) = &self.proxy {
+            writeln!(f, "proxy {}", proxy)?;
+        }
+        for (key, part) in [
+            ("eyes", &self.eyes),
+            ("eyebrows", &self.eyebrows),
+            ("eyelashes", &self.eyelashes),
+            ("hair", &self.hair),
+            ("teeth", &self.teeth),
+            ("tongue", &self.tongue),
+        ] {
+            if let Some(part) = part {
+                writeln!(f, "{} {}", key, part)?;
+            }
+        }
+        for clothes in &self.clothes {
+            writeln!(f, "clothes {}", clothes)?;
+        }
+        if let Some(skin) = &self.skin_material {
+            writeln!(f, "skinMaterial {}", skin)?;
+        }
+        for (part, mhmat) in &self.materials {
+            writeln!(f, "material {} {}", part, mhmat)?;
+        }
+        for line in &self.other {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// MakeHuman modifier name of a body morph, eg `head/head-age-decr|incr`
+///
+/// None for macro morphs, those are driven by the macrodetails sliders
+pub fn modifier_name(target: &MorphTarget) -> Option<String> {
+    if let MorphTarget::Macro(_) = target {
+        return None;
+    }
+    let (neg, pos) = (target.neg_path(), target.pos_path());
+    let group = Path::new(pos.or(neg)?).parent()?.file_name()?.to_str()?;
+    let label = match (neg.map(file_stem), pos.map(file_stem)) {
+        (Some(neg), Some(pos)) => {
+            // shared prefix up to the last dash, eg `head-age-` of `head-age-decr`
+            let common = neg
+                .bytes()
+                .zip(pos.bytes())
+                .take_while(|(a, b)| a == b)
+                .count();
+            let split = neg[..common].rfind('-').map(|i| i + 1).unwrap_or(0);
+            format!("{}|{}", neg, &pos[split..])
+        }
+        (None, Some(single)) | (Some(single), None) => single.to_string(),
+        (None, None) => return None,
+    };
+    // measure targets sit in body part folders but makehuman groups them together
+    let group = match label.starts_with("measure-") {
+        true => "measure",
+        false => group,
+    };
+    Some(format!("{}/{}", group, label))
+}
+
+const MACRO_MODIFIERS: [&str; 11] = [
+    "Gender",
+    "Age",
+    "African",
+    "Asian",
+    "Caucasian",
+    "Muscle",
+    "Weight",
+    "Height",
+    "BodyProportions",
+    "BreastSize",
+    "BreastFirmness",
+];
+
+/// Part after the group, `head-age-decr|incr` of `head/head-age-decr|incr`
+fn modifier_label(name: &str) -> &str {
+    name.rsplit_once('/').map(|(_, l)| l).unwrap_or(name)
+}
+
+fn file_stem(path: &str) -> &str {
+    Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(path)
+}
+
+/// `mixamo` of `make_human/rigs/mixamo/mixamo.rig.json`
+fn rig_name(rig: &Rig) -> &'static str {
+    file_stem(file_stem(rig.rig_json_path()))
+}
+
+/// Path relative to the MakeHuman data dir
+fn data_path(path: &str) -> &str {
+    path.strip_prefix("make_human/").unwrap_or(path)
+}
+
+/// Lowercase letters and digits only, `HighPolyEyes` and `high-poly` compare equal-ish
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Find a part by uuid, then by variant or file name
+fn find_part<T>(r: &MhmRef, file: &str) -> Option<T>
+where
+    T: IntoEnumIterator + EnumProperty + fmt::Display,
+{
+    if let Some(uuid) = &r.uuid
+        && let Some(part) = T::iter().find(|p| p.get_str("uuid") == Some(uuid.as_str()))
+    {
+        return Some(part);
+    }
+    let name = normalize(&r.name);
+    T::iter().find(|p| {
+        normalize(&p.to_string()) == name
+            || p.get_str(file).map(|f| normalize(file_stem(f))) == Some(name.clone())
+    })
+}
+
+fn resolve<T>(r: &MhmRef, file: &str, part: &mut T)
+where
+    T: IntoEnumIterator + EnumProperty + fmt::Display,
+{
+    match find_part(r, file) {
+        Some(p) => *part = p,
+        None => warn!("mhm {} {} not found", file, r),
+    }
+}
+
+/// Eyes are mesh x material, match the mesh then pick the material
+fn find_eyes(r: &MhmRef, material: Option<&str>) -> Option<Eyes> {
+    let name = normalize(&r.name);
+    let meshes: Vec<Eyes> = Eyes::iter()
+        .filter(|e| match (&r.uuid, e.get_str("uuid")) {
+            (Some(a), Some(b)) => a == b,
+            _ => name.starts_with(&normalize(file_stem(e.mhclo()))),
+        })
+        .collect();
+    meshes
+        .iter()
+        .find(|e| Some(file_stem(e.mhmat())) == material)
+        .or(meshes.first())
+        .copied()
+}
+
+fn part_ref<T: EnumProperty>(part: &T, file: &str) -> MhmRef {
+    MhmRef {
+        name: part
+            .get_str(file)
+            .map(file_stem)
+            .unwrap_or_default()
+            .to_string(),
+        uuid: part.get_str("uuid").map(str::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOB: &str = "\
+# Written by MakeHuman 1.1.1
+version v1.1.1
+name Bob
+tags male young
+camera 0.0 0.0 0.0 0.0 0.0 1.0
+modifier macrodetails/Gender 1.000000
+modifier macrodetails/Age 0.500000
+modifier head/head-age-decr|incr 0.250000
+skeleton game_engine.mhskel
+proxy male_generic 2c12f43b-1303-432c-b7ce-d78346baf2e6
+eyes HighPolyEyes 2c12f43b-1303-432c-b7ce-d78346baf2e6
+clothes tshirt02 f2e6ed5c-0c4a-4ce3-b1a1-c4c4fd8c1e51
+clothes jeans01 e0d2c0fd-1ad1-4a6b-9a64-3d6c2f4b7b0e
+skinMaterial skins/young_caucasian_male/young_caucasian_male.mhmat
+material HighPolyEyes 2c12f43b-1303-432c-b7ce-d78346baf2e6 eyes/materials/brown.mhmat
+subdivide False
+";
+
+    #[test]
+    fn test_mhm_round_trip() {
+        let parsed = MhmAsset::parse(BOB).unwrap();
+        assert_eq!(parsed.modifier("Gender"), Some(1.0));
+        assert_eq!(parsed.clothes.len(), 2);
+        assert_eq!(parsed.other.len(), 2);
+
+        let written = MhmAsset::parse(&parsed.to_string()).unwrap();
+        assert_eq!(written.modifiers, parsed.modifiers);
+        assert_eq!(written.skin_material, parsed.skin_material);
+        assert_eq!(written.proxy, parsed.proxy);
+        assert_eq!(written.eyes, parsed.eyes);
+        assert_eq!(written.clothes, parsed.clothes);
+        assert_eq!(written.materials, parsed.materials);
+        assert_eq!(written.other, parsed.other);
+    }
+
+    #[test]
+    fn test_descriptor_round_trip() {
+        let target = MorphTarget::iter()
+            .find(|t| modifier_name(t).is_some())
+            .unwrap();
+        let human = HumanDescriptor {
+            name: Some("Bob".to_string()),
+            outfit: Clothing::iter().take(1).collect(),
+            morphs: vec![Morph::new(target, 0.5)],
+            macro_sliders: Some(MacroSliders {
+                gender: 1.0,
+                age: 0.25,
+                ..default()
+            }),
+            ..default()
+        };
+
+        let mhm = MhmAsset::from_descriptor(&human);
+        assert_eq!(mhm.modifier("Gender"), Some(1.0));
+        assert_eq!(mhm.to_descriptor(), human);
+
+        let written = MhmAsset::parse(&mhm.to_string()).unwrap();
+        assert_eq!(written.materials, mhm.materials);
+        let read = written.to_descriptor();
+        assert_eq!(read.name, human.name);
+        assert_eq!(read.eyes, human.eyes);
+        assert_eq!(read.outfit, human.outfit);
+    }
+}
@@ -0,0 +1,105 @@
+//! Mirror table loader - parses the basemesh vertex symmetry table (hm08.mirror)
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use std::io::{BufRead, BufReader};
+use thiserror::Error;
+
+/// Vertex mirror table - maps each basemesh vertex to its counterpart across the YZ plane
+/// Vertices on the centre line map to themselves
+#[derive(Asset, TypePath, Debug, Default, Clone, Deref, DerefMut)]
+pub struct MirrorTable(pub Vec<u32>);
+
+impl MirrorTable {
+    /// Mirrored vertex index, vertices missing from the table map to themselves
+    pub fn mirror(&self, vertex: u32) -> u32 {
+        self.0.get(vertex as usize).copied().unwrap_or(vertex)
+    }
+
+    /// Parse hm08.mirror text, `vertex mirror side` per line
+    pub fn parse(bytes: &[u8]) -> Result<Self, MirrorTableLoaderError> {
+        let mut pairs = Vec::new();
+        let buf_reader = BufReader::new(bytes);
+
+        for (line_num, line) in buf_reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+
+            // Skip empty lines and comments
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // "vertex mirror side", side is unused
+            let mut parts = line.split_whitespace().map(|s| s.parse::<u32>());
+            match (parts.next(), parts.next()) {
+                (Some(Ok(vertex)), Some(Ok(mirror))) => pairs.push((vertex, mirror)),
+                _ => {
+                    return Err(MirrorTableLoaderError::Parse {
+                        line: line_num + 1,
+                        msg: format!("Expected vertex and mirror index, got '{}'", line),
+                    });
+                }
+            }
+        }
+
+        // identity for any vertex the file skips
+        let len = pairs.iter().map(|&(v, m)| v.max(m) + 1).max().unwrap_or(0);
+        let mut table: Vec<u32> = (0..len).collect();
+        for (vertex, mirror) in pairs {
+            table[vertex as usize] = mirror;
+        }
+
+        Ok(MirrorTable(table))
+    }
+}
+
+#[derive(Default, TypePath)]
+pub struct MirrorTableLoader;
+
+#[derive(Debug, Error)]
+pub enum MirrorTableLoaderError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Parse error on line {line}: {msg}")]
+    Parse { line: usize, msg: String },
+}
+
+impl AssetLoader for MirrorTableLoader {
+    type Asset = MirrorTable;
+    type Settings = ();
+    type Error = MirrorTableLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        MirrorTable::parse(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["mirror"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mirror_table() {
+        let table = MirrorTable::parse(b"# comment\n0 2 l\n1 1 m\n2 0 r\n5 4 l\n").unwrap();
+        assert_eq!(table.0, vec![2, 1, 0, 3, 4, 4]);
+        assert_eq!(table.mirror(0), 2);
+        assert_eq!(table.mirror(3), 3);
+        assert_eq!(table.mirror(100), 100);
+        assert!(MirrorTable::parse(b"0 x\n").is_err());
+    }
+}
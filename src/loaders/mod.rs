@@ -1,5 +1,8 @@
+mod descriptor;
 mod mhclo;
+mod mhm;
 mod mhmat;
+mod mirror;
 mod morph_target;
 mod obj_base_mesh;
 mod pose;
@@ -11,6 +14,6 @@ mod vertex_groups;
 
 #[allow(unused_imports)]
 pub use self::{
-    mhclo::*, mhmat::*, morph_target::*, obj_base_mesh::*, pose::*, proxy::*, rig::*,
-    skin_weights::*, thumb::*, vertex_groups::*,
+    descriptor::*, mhclo::*, mhm::*, mhmat::*, mirror::*, morph_target::*, obj_base_mesh::*,
+    pose::*, proxy::*, rig::*, skin_weights::*, thumb::*, vertex_groups::*,
 };
@@ -5,6 +5,8 @@ use bevy::{
 };
 use thiserror::Error;
 
+use crate::cache::hash_bytes;
+
 // Unlike normal objPlugin, we need original verts as well
 // AND we need mesh vertex indices to match obj vertex indices for mhclo binding
 #[derive(Asset, TypePath, Debug, Clone)]
@@ -14,6 +16,8 @@ pub struct ObjBaseMesh {
     pub vertices: Vec<Vec3>,
     /// Mesh vertex idx -> obj vertex idx mapping (identity for our loader)
     pub mhid_lookup: Vec<u16>,
+    /// Hash of the source .obj bytes, keys on-disk caches
+    pub hash: u64,
 }
 
 #[derive(Default, TypePath)]
@@ -123,6 +127,7 @@ impl AssetLoader for ObjBaseMeshLoader {
             mesh,
             vertices,
             mhid_lookup,
+            hash: hash_bytes(&bytes),
         })
     }
 }
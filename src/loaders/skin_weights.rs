@@ -80,6 +80,72 @@ impl SkinningWeights {
             .unwrap_or(0)
     }
 
+    /// Where each of these bones lands on `target`'s bones, for weights made for another rig
+    ///
+    /// Both files weight the same base mesh, a bone spreads over the target bones that weight
+    /// the same vertices, in proportion to how much they overlap. Shares sum to 1 per bone
+    pub fn bone_map(&self, target: &SkinningWeights) -> HashMap<String, Vec<(String, f32)>> {
+        let mut target_vertices: HashMap<usize, Vec<(&str, f32)>> = HashMap::default();
+        for (bone, weights) in &target.weights {
+            for &(vertex, weight) in weights {
+                target_vertices
+                    .entry(vertex)
+                    .or_default()
+                    .push((bone.as_str(), weight));
+            }
+        }
+
+        let mut map = HashMap::default();
+        for (bone, weights) in &self.weights {
+            let mut overlap: HashMap<&str, f32> = HashMap::default();
+            for (vertex, weight) in weights {
+                for &(target_bone, target_weight) in
+                    target_vertices.get(vertex).into_iter().flatten()
+                {
+                    *overlap.entry(target_bone).or_default() += weight * target_weight;
+                }
+            }
+            let sum: f32 = overlap.values().sum();
+            if sum <= 1e-6 {
+                continue;
+            }
+            let mut shares: Vec<(String, f32)> = overlap
+                .into_iter()
+                .map(|(target_bone, w)| (target_bone.to_string(), w / sum))
+                .collect();
+            shares.sort_by(|a, b| b.1.total_cmp(&a.1));
+            map.insert(bone.clone(), shares);
+        }
+        map
+    }
+
+    /// Rename bones through a [`SkinningWeights::bone_map`], bones it lacks are kept as is
+    pub fn remap_bones(&self, bone_map: &HashMap<String, Vec<(String, f32)>>) -> SkinningWeights {
+        let mut remapped: HashMap<String, HashMap<usize, f32>> = HashMap::default();
+        for (bone, weights) in &self.weights {
+            let shares = match bone_map.get(bone) {
+                Some(shares) => shares.clone(),
+                None => vec![(bone.clone(), 1.0)],
+            };
+            for (target_bone, share) in shares {
+                let target = remapped.entry(target_bone).or_default();
+                for &(vertex, weight) in weights {
+                    *target.entry(vertex).or_default() += weight * share;
+                }
+            }
+        }
+        SkinningWeights {
+            weights: remapped
+                .into_iter()
+                .map(|(bone, weights)| {
+                    let mut weights: Vec<(usize, f32)> = weights.into_iter().collect();
+                    weights.sort_by_key(|(vertex, _)| *vertex);
+                    (bone, weights)
+                })
+                .collect(),
+        }
+    }
+
     /// Get weights for a specific bone
     pub fn bone_weights(&self, bone_name: &str) -> Option<&Vec<(usize, f32)>> {
         self.weights.get(bone_name)
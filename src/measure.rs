@@ -0,0 +1,696 @@
+//! Body measurements of a built human, in centimeters
+//!
+//! Measured like MakeHuman's `Ruler`: each `measure-*` modifier has a list of base mesh vertices,
+//! the measurement is the length of the path through them on the morphed body.
+//! A ruler whose vertices are missing from the mesh measures 0
+//!
+//! [`MeasurementGoal`] runs [`fit_measurements`] to morph a human to given measurements
+
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, futures_lite::future},
+};
+use std::path::Path;
+use strum::IntoEnumIterator;
+
+use crate::{
+    BaseMesh,
+    assets::*,
+    bake::morph_vertices,
+    components::*,
+    loaders::{MorphTargetData, VertexGroups},
+};
+
+/// MakeHuman's ruler vertex paths (hm08 indices) by measure modifier, a path that ends on its start is a loop
+pub const RULERS: [(&str, &[u32]); 19] = [
+    (
+        "measure-neck-circ",
+        &[
+            7514, 10358, 7631, 7496, 7488, 7489, 7474, 7475, 7531, 7537, 7543, 7549, 7555, 7561,
+            7743, 7722, 856, 1030, 1051, 850, 844, 838, 832, 826, 820, 756, 755, 770, 769, 777,
+            929, 3690, 804, 800, 808, 801, 799, 803, 7513, 7515, 7521, 7514,
+        ],
+    ),
+    (
+        "measure-neck-height",
+        &[853, 854, 855, 11837, 11838, 11839, 11840, 11841, 11842],
+    ),
+    (
+        "measure-upperarm-circ",
+        &[
+            8383, 8393, 8392, 8391, 8390, 8394, 8395, 8399, 10455, 10516, 8396, 8397, 8398, 8388,
+            8387, 8386, 10431, 8385, 8384, 8389,
+        ],
+    ),
+    ("measure-upperarm-length", &[8274, 10037]),
+    ("measure-lowerarm-length", &[10040, 10548]),
+    (
+        "measure-wrist-circ",
+        &[
+            10208, 10211, 10212, 10216, 10471, 10533, 10213, 10214, 10215, 10205, 10203, 10206,
+            10210, 10209,
+        ],
+    ),
+    ("measure-frontchest-dist", &[1437, 8125]),
+    (
+        "measure-bust-circ",
+        &[
+            8439, 8455, 8462, 8446, 8478, 8494, 8557, 8510, 8526, 8542, 10720, 10601, 10603, 10602,
+            10612, 10611, 10610, 10613, 10604, 10605, 10606, 3942, 3941, 3940, 3950, 3947, 3948,
+            3949, 3938, 3939, 3937, 4065, 1870, 1854, 1838, 1885, 1822, 1806, 1774, 1790, 1783,
+            1767, 1799, 8471,
+        ],
+    ),
+    ("measure-napetowaist-dist", &[1491, 4181]),
+    ("measure-waisttohip-dist", &[4121, 10759]),
+    ("measure-shoulder-dist", &[7478, 8275]),
+    (
+        "measure-waist-circ",
+        &[
+            4121, 10760, 10757, 10777, 10776, 10779, 10780, 10778, 10781, 10771, 10773, 10772,
+            10775, 10774, 10814, 10834, 10816, 10817, 10818, 10819, 10820, 10821, 4181, 4180, 4179,
+            4178, 4177, 4176, 4175, 4196, 4173, 4131, 4132, 4129, 4130, 4128, 4138, 4135, 4137,
+            4136, 4133, 4134, 4108, 4113, 4118, 4121,
+        ],
+    ),
+    (
+        "measure-hips-circ",
+        &[
+            4341, 10968, 10969, 10971, 10970, 10967, 10928, 10927, 10925, 10926, 10923, 10924,
+            10868, 10875, 10861, 10862, 4228, 4227, 4226, 4242, 4234, 4294, 4293, 4296, 4295, 4297,
+            4298, 4342, 4345, 4346, 4344, 4343, 4361, 4341,
+        ],
+    ),
+    ("measure-upperleg-height", &[10970, 11230]),
+    (
+        "measure-thigh-circ",
+        &[
+            11071, 11080, 11081, 11086, 11076, 11077, 11074, 11075, 11072, 11073, 11069, 11070,
+            11087, 11085, 11084, 12994, 11083, 11082, 11079, 11071,
+        ],
+    ),
+    (
+        "measure-knee-circ",
+        &[
+            11249, 11231, 11232, 11233, 11234, 11235, 11236, 11237, 11238, 11239, 11240, 11241,
+            11242, 11243, 11244, 11245, 11246, 11247, 11248, 11249,
+        ],
+    ),
+    ("measure-lowerleg-height", &[11225, 12820]),
+    (
+        "measure-calf-circ",
+        &[
+            11339, 11336, 11353, 11351, 11350, 13008, 11349, 11348, 11345, 11337, 11344, 11346,
+            11347, 11352, 11342, 11343, 11340, 11341, 11339,
+        ],
+    ),
+    (
+        "measure-ankle-circ",
+        &[
+            11460, 11464, 11458, 11459, 11419, 11418, 12958, 12965, 12960, 12963, 12961, 12962,
+            12964, 12927, 12928, 12929, 12930, 11421, 11420, 11457, 11460,
+        ],
+    ),
+];
+
+/// Vertex path of a ruler in [`RULERS`]
+pub fn ruler(name: &str) -> Option<&'static [u32]> {
+    RULERS.iter().find(|(n, _)| *n == name).map(|(_, r)| *r)
+}
+
+/// Length of the path through the `ruler` vertices, `None` if one is missing
+pub fn ruler_length(vertices: &[Vec3], ruler: &[u32]) -> Option<f32> {
+    let points = ruler
+        .iter()
+        .map(|&i| vertices.get(i as usize).copied())
+        .collect::<Option<Vec<_>>>()?;
+    Some(points.windows(2).map(|w| w[0].distance(w[1])).sum())
+}
+
+/// Body measurements in centimeters, updated after each body rebuild
+///
+/// Taken from the base mesh before any clothing, for sizing and stat displays.
+/// Limbs are measured on the left side
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct HumanMeasurements {
+    /// Floor to top of head
+    pub height: f32,
+    pub neck: f32,
+    pub neck_height: f32,
+    /// Around the bust
+    pub chest: f32,
+    /// Across the front of the chest
+    pub front_chest: f32,
+    pub waist: f32,
+    pub hips: f32,
+    /// Nape of the neck down the back to the waist
+    pub nape_to_waist: f32,
+    pub waist_to_hip: f32,
+    /// Shoulder to shoulder across the back
+    pub shoulder_width: f32,
+    pub upper_arm_length: f32,
+    pub forearm_length: f32,
+    /// Shoulder to wrist
+    pub arm_length: f32,
+    pub upper_arm: f32,
+    pub wrist: f32,
+    /// Floor to the top of the upper leg ruler
+    pub inseam: f32,
+    pub upper_leg_height: f32,
+    pub lower_leg_height: f32,
+    pub thigh: f32,
+    pub knee: f32,
+    pub calf: f32,
+    pub ankle: f32,
+}
+
+/// Vertices of the base mesh `body` group, leaving out helpers (tights, skirt, hair)
+pub fn body_vertices(morphed_vertices: &[Vec3], vertex_groups: &VertexGroups) -> Vec<Vec3> {
+    match vertex_groups.get("body") {
+        Some(ranges) => VertexGroups::expand_ranges(ranges)
+            .into_iter()
+            .filter_map(|i| morphed_vertices.get(i).copied())
+            .collect(),
+        None => morphed_vertices.to_vec(),
+    }
+}
+
+/// Measure the morphed base mesh with [`RULERS`], height from [`body_vertices`]
+pub fn measure_body(morphed_vertices: &[Vec3], body_vertices: &[Vec3]) -> HumanMeasurements {
+    let cm = |name: &str| {
+        ruler(name)
+            .and_then(|r| ruler_length(morphed_vertices, r))
+            .map_or(0.0, |m| m * 100.0)
+    };
+
+    let mut m = HumanMeasurements {
+        neck: cm("measure-neck-circ"),
+        neck_height: cm("measure-neck-height"),
+        chest: cm("measure-bust-circ"),
+        front_chest: cm("measure-frontchest-dist"),
+        waist: cm("measure-waist-circ"),
+        hips: cm("measure-hips-circ"),
+        nape_to_waist: cm("measure-napetowaist-dist"),
+        waist_to_hip: cm("measure-waisttohip-dist"),
+        shoulder_width: cm("measure-shoulder-dist"),
+        upper_arm_length: cm("measure-upperarm-length"),
+        forearm_length: cm("measure-lowerarm-length"),
+        upper_arm: cm("measure-upperarm-circ"),
+        wrist: cm("measure-wrist-circ"),
+        upper_leg_height: cm("measure-upperleg-height"),
+        lower_leg_height: cm("measure-lowerleg-height"),
+        thigh: cm("measure-thigh-circ"),
+        knee: cm("measure-knee-circ"),
+        calf: cm("measure-calf-circ"),
+        ankle: cm("measure-ankle-circ"),
+        ..default()
+    };
+    m.arm_length = m.upper_arm_length + m.forearm_length;
+
+    let (min_y, max_y) = body_vertices
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(min, max), v| {
+            (min.min(v.y), max.max(v.y))
+        });
+    if min_y > max_y {
+        return m;
+    }
+    m.height = (max_y - min_y) * 100.0;
+
+    // lower leg ruler ends at the ankle
+    let ankle =
+        ruler("measure-lowerleg-height").and_then(|r| morphed_vertices.get(*r.last()? as usize));
+    if let Some(ankle) = ankle {
+        m.inseam = m.upper_leg_height + m.lower_leg_height + (ankle.y - min_y) * 100.0;
+    }
+
+    m
+}
+
+/// Wanted measurements in centimeters, None leaves that one free
+///
+/// On a [`Human`] this runs [`fit_measurements`] once its targets load,
+/// writes the result to [`MacroSliders`] and [`Morphs`] and removes itself
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct MeasurementGoal {
+    pub height: Option<f32>,
+    pub chest: Option<f32>,
+    pub waist: Option<f32>,
+    pub hips: Option<f32>,
+    /// Allowed error in centimeters
+    pub tolerance: f32,
+}
+
+impl Default for MeasurementGoal {
+    fn default() -> Self {
+        Self {
+            height: None,
+            chest: None,
+            waist: None,
+            hips: None,
+            tolerance: 0.5,
+        }
+    }
+}
+
+/// Result of [`fit_measurements`]
+#[derive(Clone, Debug)]
+pub struct MeasurementFit {
+    pub sliders: MacroSliders,
+    /// Input morphs with the measure morphs set, ethnic macros moved to `sliders`
+    pub morphs: Vec<Morph>,
+    pub measurements: HumanMeasurements,
+    /// Every goal is within tolerance
+    pub converged: bool,
+}
+
+/// Rounds over all goals before giving up
+const FIT_ROUNDS: usize = 12;
+
+/// Parameter step used to estimate how a measurement responds
+const FIT_PROBE: f32 = 0.05;
+
+/// What the solver moves for a goal
+#[derive(Clone, Copy, Debug)]
+enum FitParam {
+    Height,
+    Measure(MorphTarget),
+}
+
+impl FitParam {
+    fn get(&self, fit: &MeasurementFit) -> f32 {
+        match self {
+            FitParam::Height => fit.sliders.height,
+            FitParam::Measure(target) => fit
+                .morphs
+                .iter()
+                .find(|m| m.target == *target)
+                .map_or(0.0, |m| m.value),
+        }
+    }
+
+    fn set(&self, fit: &mut MeasurementFit, value: f32) {
+        match self {
+            FitParam::Height => fit.sliders.height = value.clamp(0.0, 1.0),
+            FitParam::Measure(target) => {
+                match fit.morphs.iter_mut().find(|m| m.target == *target) {
+                    Some(morph) => *morph = Morph::new(*target, value),
+                    None => fit.morphs.push(Morph::new(*target, value)),
+                }
+            }
+        }
+    }
+
+    fn range(&self) -> (f32, f32) {
+        match self {
+            FitParam::Height => (0.0, 1.0),
+            FitParam::Measure(target) => target.value_range(),
+        }
+    }
+}
+
+/// The `<name>-decr|incr` measure morph
+fn measure_morph(name: &str) -> Option<MorphTarget> {
+    let incr = format!("{}-incr", name);
+    MorphTarget::iter().find(|t| {
+        t.pos_path()
+            .and_then(|p| Path::new(p).file_stem()?.to_str())
+            .is_some_and(|stem| stem == incr)
+    })
+}
+
+/// Measure morphs [`fit_measurements`] moves for chest, waist and hips
+fn fit_morphs() -> [Option<MorphTarget>; 3] {
+    [
+        "measure-bust-circ",
+        "measure-waist-circ",
+        "measure-hips-circ",
+    ]
+    .map(measure_morph)
+}
+
+/// Every target file [`fit_measurements`] may apply for `goal` on a human with `sliders` and `morphs`
+///
+/// Macro targets of the current sliders, over the whole height range when height is a goal,
+/// and the measure morphs of the goals that are set
+pub fn fit_target_paths(
+    goal: &MeasurementGoal,
+    sliders: &MacroSliders,
+    morphs: &[Morph],
+) -> Vec<&'static str> {
+    // ends of the height range cover every height target in between
+    let heights = match goal.height {
+        Some(_) => vec![0.0, 1.0],
+        None => vec![sliders.height],
+    };
+    let [chest, waist, hips] = fit_morphs();
+    let mut paths: Vec<&'static str> = heights
+        .into_iter()
+        .flat_map(|height| MacroSliders { height, ..*sliders }.target_weights())
+        .chain(morph_target_weights(morphs, None))
+        .map(|(p, _)| p)
+        .chain(
+            [(goal.chest, chest), (goal.waist, waist), (goal.hips, hips)]
+                .into_iter()
+                .filter_map(|(want, target)| want.and(target))
+                .flat_map(|t| [t.pos_path(), t.neg_path()])
+                .flatten(),
+        )
+        .collect();
+    paths.sort_unstable();
+    paths.dedup();
+    paths
+}
+
+/// Adjust macro height and the measure morphs until the body hits `goal`
+///
+/// Each round steps every goal's parameter by the slope measured with a small probe.
+/// `target` returns loaded target data by asset path, see [`fit_target_paths`], missing targets are left out.
+pub fn fit_measurements<'a>(
+    goal: &MeasurementGoal,
+    sliders: MacroSliders,
+    morphs: &[Morph],
+    base_vertices: &[Vec3],
+    vertex_groups: &VertexGroups,
+    target: impl Fn(&str) -> Option<&'a MorphTargetData>,
+) -> MeasurementFit {
+    let measure = |fit: &MeasurementFit| {
+        let weights = morph_target_weights(&fit.morphs, Some(&fit.sliders));
+        let morphed = morph_vertices(
+            base_vertices,
+            weights
+                .iter()
+                .filter_map(|(path, weight)| Some((target(path)?, *weight))),
+        );
+        measure_body(&morphed, &body_vertices(&morphed, vertex_groups))
+    };
+
+    // ethnic macros would count twice next to the sliders
+    let mut fit = MeasurementFit {
+        sliders,
+        morphs: morphs
+            .iter()
+            .filter(|m| !is_ethnic_macro(&m.target))
+            .cloned()
+            .collect(),
+        measurements: HumanMeasurements::default(),
+        converged: false,
+    };
+    fit.measurements = measure(&fit);
+
+    let [chest, waist, hips] = fit_morphs().map(|t| t.map(FitParam::Measure));
+    let wanted: [(
+        &str,
+        Option<f32>,
+        fn(&HumanMeasurements) -> f32,
+        Option<FitParam>,
+    ); 4] = [
+        ("height", goal.height, |m| m.height, Some(FitParam::Height)),
+        ("chest", goal.chest, |m| m.chest, chest),
+        ("waist", goal.waist, |m| m.waist, waist),
+        ("hips", goal.hips, |m| m.hips, hips),
+    ];
+    let mut goals = vec![];
+    for (name, value, get, param) in wanted {
+        match (value, param) {
+            (Some(value), Some(param)) => goals.push((value, get, param)),
+            (Some(_), None) => warn!("No measure morph to fit {}", name),
+            _ => {}
+        }
+    }
+
+    let within = |fit: &MeasurementFit| {
+        goals
+            .iter()
+            .all(|(want, get, _)| (get(&fit.measurements) - want).abs() <= goal.tolerance)
+    };
+    for _ in 0..FIT_ROUNDS {
+        if within(&fit) {
+            break;
+        }
+        for &(want, get, param) in &goals {
+            let error = want - get(&fit.measurements);
+            if error.abs() <= goal.tolerance {
+                continue;
+            }
+            let value = param.get(&fit);
+            let (min, max) = param.range();
+            let probe_step = match value + FIT_PROBE <= max {
+                true => FIT_PROBE,
+                false => -FIT_PROBE,
+            };
+            let mut probe = fit.clone();
+            param.set(&mut probe, value + probe_step);
+            let slope = (get(&measure(&probe)) - get(&fit.measurements)) / probe_step;
+            if slope.abs() < 1e-3 {
+                continue;
+            }
+            param.set(&mut fit, (value + error / slope).clamp(min, max));
+            fit.measurements = measure(&fit);
+        }
+    }
+    fit.converged = within(&fit);
+    fit
+}
+
+/// Targets loading for a [`MeasurementGoal`]
+#[derive(Component)]
+pub(crate) struct MeasurementFitAssets(Vec<(&'static str, Handle<MorphTargetData>)>);
+
+/// [`fit_measurements`] running for a [`MeasurementGoal`]
+#[derive(Component)]
+pub(crate) struct MeasurementFitTask(Task<MeasurementFit>);
+
+/// Load the targets for new or changed goals, restarting any running fit
+pub(crate) fn start_measurement_fits(
+    mut commands: Commands,
+    query: Query<
+        (Entity, &MeasurementGoal, &Morphs, Option<&MacroSliders>),
+        Changed<MeasurementGoal>,
+    >,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, goal, morphs, sliders) in query.iter() {
+        let sliders = sliders
+            .copied()
+            .unwrap_or_else(|| MacroSliders::from_ethnic_morphs(morphs));
+        let assets = fit_target_paths(goal, &sliders, morphs)
+            .into_iter()
+            .map(|path| (path, asset_server.load(path)))
+            .collect();
+        commands
+            .entity(entity)
+            .remove::<MeasurementFitTask>()
+            .insert(MeasurementFitAssets(assets));
+    }
+}
+
+/// Start the fit once every target loaded or failed
+pub(crate) fn run_measurement_fits(
+    mut commands: Commands,
+    query: Query<(
+        Entity,
+        &MeasurementGoal,
+        &MeasurementFitAssets,
+        &Morphs,
+        Option<&MacroSliders>,
+    )>,
+    asset_server: Res<AssetServer>,
+    targets: Res<Assets<MorphTargetData>>,
+    base_mesh: Res<BaseMesh>,
+) {
+    for (entity, goal, assets, morphs, sliders) in query.iter() {
+        let done = assets.0.iter().all(|(_, h)| {
+            asset_server.is_loaded_with_dependencies(h.id())
+                || asset_server.load_state(h.id()).is_failed()
+        });
+        if !done {
+            continue;
+        }
+
+        let data: HashMap<&'static str, MorphTargetData> = assets
+            .0
+            .iter()
+            .filter_map(|(path, h)| Some((*path, targets.get(h)?.clone())))
+            .collect();
+        let goal = *goal;
+        let sliders = sliders
+            .copied()
+            .unwrap_or_else(|| MacroSliders::from_ethnic_morphs(morphs));
+        let morphs = morphs.0.clone();
+        let base_vertices = base_mesh.vertices.clone();
+        let vertex_groups = base_mesh.vertex_groups.clone();
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            fit_measurements(
+                &goal,
+                sliders,
+                &morphs,
+                &base_vertices,
+                &vertex_groups,
+                |path| data.get(path),
+            )
+        });
+        commands
+            .entity(entity)
+            .remove::<MeasurementFitAssets>()
+            .insert(MeasurementFitTask(task));
+    }
+}
+
+/// Apply finished fits, the human then rebuilds from the new sliders and morphs
+pub(crate) fn finish_measurement_fits(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut MeasurementFitTask)>,
+) {
+    for (entity, mut task) in query.iter_mut() {
+        let Some(fit) = future::block_on(future::poll_once(&mut task.0)) else {
+            continue;
+        };
+        if !fit.converged {
+            warn!("Measurement goal not reached, got {:?}", fit.measurements);
+        }
+        commands
+            .entity(entity)
+            .remove::<(MeasurementGoal, MeasurementFitTask)>()
+            .insert((fit.sliders, Morphs(fit.morphs)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ruler_length_follows_path() {
+        let vertices = [Vec3::ZERO, Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y];
+        assert_eq!(ruler_length(&vertices, &[0, 1, 2, 3, 0]), Some(4.0));
+        assert_eq!(ruler_length(&vertices, &[0, 4]), None);
+    }
+
+    #[test]
+    fn test_measure_body_waist_loop() {
+        // waist ruler around a circle of 15cm radius, everything else at the origin
+        let waist = ruler("measure-waist-circ").unwrap();
+        assert_eq!(waist.first(), waist.last());
+        let sides = waist.len() - 1;
+        let radius = 0.15;
+        let mut vertices = vec![Vec3::ZERO; 14000];
+        for (k, &i) in waist[..sides].iter().enumerate() {
+            let angle = std::f32::consts::TAU * k as f32 / sides as f32;
+            vertices[i as usize] = Vec3::new(radius * angle.cos(), 1.0, radius * angle.sin());
+        }
+        let body = [Vec3::ZERO, Vec3::new(0.0, 1.75, 0.0)];
+
+        let m = measure_body(&vertices, &body);
+        let polygon = sides as f32 * 2.0 * radius * (std::f32::consts::PI / sides as f32).sin();
+        assert!(
+            (m.waist - polygon * 100.0).abs() < 0.01,
+            "waist {}",
+            m.waist
+        );
+        assert!((m.height - 175.0).abs() < 0.01, "height {}", m.height);
+        assert_eq!(m.hips, 0.0);
+
+        // rulers past the end of the mesh measure 0
+        let m = measure_body(&vertices[..100], &body);
+        assert_eq!(m.waist, 0.0);
+        assert!((m.height - 175.0).abs() < 0.01);
+    }
+
+    /// Two vertex body, the height macro targets move the top one 20cm up or down
+    fn height_targets() -> (Vec<Vec3>, MorphTargetData, MorphTargetData) {
+        let offset = |y: f32| MorphTargetData {
+            offsets: [(1, Vec3::new(0.0, y, 0.0))].into_iter().collect(),
+        };
+        (
+            vec![Vec3::ZERO, Vec3::new(0.0, 1.7, 0.0)],
+            offset(0.2),
+            offset(-0.2),
+        )
+    }
+
+    #[test]
+    fn test_fit_measurements_height_converges() {
+        let (base, max, min) = height_targets();
+        let target = |path: &str| match path {
+            p if p.ends_with("-maxheight.target") => Some(&max),
+            p if p.ends_with("-minheight.target") => Some(&min),
+            _ => None,
+        };
+        let goal = MeasurementGoal {
+            height: Some(180.0),
+            ..default()
+        };
+
+        let fit = fit_measurements(
+            &goal,
+            MacroSliders::default(),
+            &[],
+            &base,
+            &VertexGroups::default(),
+            target,
+        );
+        assert!(fit.converged, "height {}", fit.measurements.height);
+        assert!((fit.measurements.height - 180.0).abs() <= goal.tolerance);
+        assert!(fit.sliders.height > 0.5);
+    }
+
+    #[test]
+    fn test_fit_measurements_reduces_error() {
+        let (base, max, min) = height_targets();
+        let target = |path: &str| match path {
+            p if p.ends_with("-maxheight.target") => Some(&max),
+            p if p.ends_with("-minheight.target") => Some(&min),
+            _ => None,
+        };
+        // out of reach, the tallest body is 190cm
+        let goal = MeasurementGoal {
+            height: Some(250.0),
+            ..default()
+        };
+        let start = 170.0;
+
+        let fit = fit_measurements(
+            &goal,
+            MacroSliders::default(),
+            &[],
+            &base,
+            &VertexGroups::default(),
+            target,
+        );
+        assert!(!fit.converged);
+        assert!((250.0 - fit.measurements.height) < (250.0 - start));
+        assert_eq!(fit.sliders.height, 1.0);
+    }
+
+    #[test]
+    fn test_fit_target_paths_only_needed() {
+        let sliders = MacroSliders::default();
+        let is_height = |p: &&str| p.ends_with("height.target");
+
+        let free = fit_target_paths(&MeasurementGoal::default(), &sliders, &[]);
+        assert!(!free.is_empty());
+        assert!(!free.iter().any(is_height));
+
+        let height = MeasurementGoal {
+            height: Some(180.0),
+            ..default()
+        };
+        let paths = fit_target_paths(&height, &sliders, &[]);
+        assert!(paths.iter().any(|p| p.ends_with("-minheight.target")));
+        assert!(paths.iter().any(|p| p.ends_with("-maxheight.target")));
+        assert!(free.iter().all(|p| paths.contains(p)));
+
+        let all = MacroMorph::iter()
+            .flat_map(|m| {
+                let (min, avg, max) = m.paths();
+                [min, avg, max]
+            })
+            .flatten()
+            .count();
+        assert!(paths.len() < all);
+    }
+}
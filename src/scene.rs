@@ -0,0 +1,97 @@
+//! Save humans in DynamicScenes without the bones, meshes and parts the build spawns
+//!
+//! Loaded humans get [`HumanDirty`] from [`Human`]'s required components and rebuild
+
+use crate::{components::*, measure::HumanMeasurements};
+use bevy::{
+    mesh::{morph::MeshMorphWeights, skinning::SkinnedMesh},
+    prelude::*,
+};
+
+/// Component filter dropping what a human build regenerates
+pub fn human_scene_filter() -> SceneFilter {
+    SceneFilter::allow_all()
+        .deny::<Mesh3d>()
+        .deny::<MeshMaterial3d<StandardMaterial>>()
+        .deny::<SkinnedMesh>()
+        .deny::<MeshMorphWeights>()
+        .deny::<AnimationPlayer>()
+        .deny::<HumanBuildProgress>()
+        .deny::<HumanMeasurements>()
+        // bones and parts, user children come back through their ChildOf
+        .deny::<Children>()
+}
+
+/// Scene of `entities`, skipping MakeHuman spawned entities (bones, parts, colliders)
+///
+/// Children of bones are not kept, parent props to the human or re-attach them after loading
+pub fn human_scene(world: &World, entities: impl IntoIterator<Item = Entity>) -> DynamicScene {
+    let entities = entities.into_iter().filter(|&e| {
+        world.get::<MHTag>(e).is_none()
+            && world
+                .get::<ChildOf>(e)
+                .is_none_or(|c| world.get::<MHTag>(c.parent()).is_none())
+    });
+    DynamicSceneBuilder::from_world(world)
+        .with_component_filter(human_scene_filter())
+        .extract_entities(entities)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_scene_skips_build_output() {
+        let mut world = World::new();
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Human>();
+            registry.register::<Morphs>();
+            registry.register::<HumanBuildProgress>();
+            registry.register::<HumanMeasurements>();
+            registry.register::<MHTag>();
+            registry.register::<Name>();
+            registry.register::<Children>();
+            registry.register::<ChildOf>();
+        }
+        world.insert_resource(registry);
+
+        let human = world
+            .spawn((
+                Human,
+                HumanBuildProgress::Complete,
+                HumanMeasurements::default(),
+            ))
+            .id();
+        let bone = world.spawn((MHTag::Armature, ChildOf(human))).id();
+        let bone_prop = world.spawn((Name::new("sword"), ChildOf(bone))).id();
+        let prop = world.spawn((Name::new("hat"), ChildOf(human))).id();
+
+        let scene = human_scene(&world, [human, bone, bone_prop, prop]);
+        let ids: Vec<_> = scene.entities.iter().map(|e| e.entity).collect();
+        assert_eq!(ids, vec![human, prop]);
+
+        let has = |entity: Entity, path: &str| {
+            scene
+                .entities
+                .iter()
+                .find(|e| e.entity == entity)
+                .unwrap()
+                .components
+                .iter()
+                .any(|c| {
+                    c.get_represented_type_info()
+                        .is_some_and(|info| info.type_path() == path)
+                })
+        };
+        assert!(has(human, Human::type_path()));
+        assert!(has(human, Morphs::type_path()));
+        assert!(!has(human, HumanBuildProgress::type_path()));
+        assert!(!has(human, HumanMeasurements::type_path()));
+        assert!(!has(human, Children::type_path()));
+        assert!(has(prop, ChildOf::type_path()));
+    }
+}
@@ -0,0 +1,202 @@
+//! Left/right symmetry for morphs and morph target data
+//!
+//! Sided targets are named `l-*`/`r-*` (arms, legs, face parts) or `*-l`/`*-r` (asym).
+//! A pair whose two ends are opposite sides of one target, like `asym-brown-1-l|r`, mirrors by negating its value.
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{
+    assets::{Morph, MorphTarget},
+    loaders::{MirrorTable, MorphTargetData},
+};
+
+/// Side of the body, from the human's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    pub fn opposite(self) -> Self {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+}
+
+/// What a target becomes on the other side of the body
+enum Counterpart {
+    /// Centre target, mirrors to itself
+    Centre,
+    /// Sided target with a twin on the other side
+    Target(MorphTarget),
+    /// Target spanning both sides, mirrors to its negated value
+    Negate,
+}
+
+/// Side a target file's name puts it on
+fn path_side(path: &str) -> Option<Side> {
+    let stem = path.rsplit('/').next()?.strip_suffix(".target")?;
+    if stem.starts_with("l-") || stem.ends_with("-l") {
+        Some(Side::Left)
+    } else if stem.starts_with("r-") || stem.ends_with("-r") {
+        Some(Side::Right)
+    } else {
+        None
+    }
+}
+
+/// Target file path with its side swapped, `None` for centre targets
+fn swap_side(path: &str) -> Option<String> {
+    let (dir, file) = match path.rsplit_once('/') {
+        Some((dir, file)) => (Some(dir), file),
+        None => (None, path),
+    };
+    let stem = file.strip_suffix(".target")?;
+    let swapped = if let Some(rest) = stem.strip_prefix("l-") {
+        format!("r-{}", rest)
+    } else if let Some(rest) = stem.strip_prefix("r-") {
+        format!("l-{}", rest)
+    } else if let Some(rest) = stem.strip_suffix("-l") {
+        format!("{}-r", rest)
+    } else if let Some(rest) = stem.strip_suffix("-r") {
+        format!("{}-l", rest)
+    } else {
+        return None;
+    };
+    Some(match dir {
+        Some(dir) => format!("{}/{}.target", dir, swapped),
+        None => format!("{}.target", swapped),
+    })
+}
+
+/// Every target keyed by its (pos, neg) paths
+fn targets_by_path() -> HashMap<(Option<&'static str>, Option<&'static str>), MorphTarget> {
+    MorphTarget::iter()
+        .map(|t| ((t.pos_path(), t.neg_path()), t))
+        .collect()
+}
+
+fn counterpart(
+    target: &MorphTarget,
+    lookup: &HashMap<(Option<&'static str>, Option<&'static str>), MorphTarget>,
+) -> Counterpart {
+    let (pos, neg) = (target.pos_path(), target.neg_path());
+    let (swapped_pos, swapped_neg) = (pos.and_then(swap_side), neg.and_then(swap_side));
+    if swapped_pos.is_none() && swapped_neg.is_none() {
+        return Counterpart::Centre;
+    }
+    if swapped_pos.as_deref() == neg && swapped_neg.as_deref() == pos {
+        return Counterpart::Negate;
+    }
+    let key = (
+        swapped_pos.as_deref().or(pos),
+        swapped_neg.as_deref().or(neg),
+    );
+    match lookup.get(&key) {
+        Some(&twin) => Counterpart::Target(twin),
+        None => Counterpart::Centre,
+    }
+}
+
+/// Side a target acts on, `None` for centre targets and targets spanning both sides
+pub fn target_side(target: &MorphTarget) -> Option<Side> {
+    let pos = target.pos_path().map(path_side);
+    let neg = target.neg_path().map(path_side);
+    match (pos, neg) {
+        (Some(side), None) | (None, Some(side)) => side,
+        (Some(pos), Some(neg)) if pos == neg => pos,
+        _ => None,
+    }
+}
+
+/// Mirror a morph set across the body's centre line
+///
+/// Sided morphs move to their twin on the other side, asym morphs flip sign, centre morphs stay.
+pub fn mirror_morphs(morphs: &[Morph]) -> Vec<Morph> {
+    let lookup = targets_by_path();
+    morphs
+        .iter()
+        .map(|morph| match counterpart(&morph.target, &lookup) {
+            Counterpart::Centre => morph.clone(),
+            Counterpart::Target(twin) => Morph::new(twin, morph.value),
+            Counterpart::Negate => Morph::new(morph.target, -morph.value),
+        })
+        .collect()
+}
+
+/// Make a morph set symmetric by copying the `from` side onto the other side
+///
+/// Morphs on the other side are replaced, asym morphs are dropped, centre morphs stay.
+pub fn symmetrize_morphs(morphs: &[Morph], from: Side) -> Vec<Morph> {
+    let lookup = targets_by_path();
+    let mut out = Vec::with_capacity(morphs.len());
+    for morph in morphs {
+        match counterpart(&morph.target, &lookup) {
+            Counterpart::Centre => out.push(morph.clone()),
+            Counterpart::Negate => {}
+            Counterpart::Target(twin) => {
+                if target_side(&morph.target) == Some(from) {
+                    out.push(morph.clone());
+                    out.push(Morph::new(twin, morph.value));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Mirror morph target data across the YZ plane, for sided targets with no shipped twin
+pub fn mirror_target_data(data: &MorphTargetData, mirror: &MirrorTable) -> MorphTargetData {
+    MorphTargetData {
+        offsets: data
+            .offsets
+            .iter()
+            .map(|(&vertex, &offset)| {
+                (
+                    mirror.mirror(vertex),
+                    Vec3::new(-offset.x, offset.y, offset.z),
+                )
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirror_morphs_twice_is_identity() {
+        let morphs: Vec<Morph> = MorphTarget::iter().map(|t| Morph::new(t, 0.5)).collect();
+        assert_eq!(mirror_morphs(&mirror_morphs(&morphs)), morphs);
+    }
+
+    #[test]
+    fn test_mirror_target_data_twice_is_identity() {
+        let mirror = MirrorTable(vec![2, 1, 0]);
+        let data = MorphTargetData {
+            offsets: [(0, Vec3::new(0.1, 0.2, 0.3)), (1, Vec3::X)]
+                .into_iter()
+                .collect(),
+        };
+        let mirrored = mirror_target_data(&data, &mirror);
+        assert_eq!(mirrored.offsets[&2], Vec3::new(-0.1, 0.2, 0.3));
+        assert_eq!(mirror_target_data(&mirrored, &mirror).offsets, data.offsets);
+    }
+
+    #[test]
+    fn test_swap_side() {
+        assert_eq!(
+            swap_side("armslegs/l-hand-fingers-decr.target").as_deref(),
+            Some("armslegs/r-hand-fingers-decr.target")
+        );
+        assert_eq!(
+            swap_side("asym-brown-1-r.target").as_deref(),
+            Some("asym-brown-1-l.target")
+        );
+        assert_eq!(swap_side("head/head-age-decr.target"), None);
+    }
+}
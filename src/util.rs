@@ -7,7 +7,11 @@ use bevy::{
     prelude::*,
 };
 
-use crate::{loaders::*, skeleton::Skeleton};
+use crate::{
+    components::{SkinInfluences, WeightPruning},
+    loaders::*,
+    skeleton::Skeleton,
+};
 
 /// Apply skinning weights to proxy mesh via barycentric interpolation
 /// Proxy vertices map to base mesh triangles, so we blend weights from 3 base verts
@@ -17,6 +21,7 @@ pub fn apply_skinning_weights_to_proxy(
     mhid_lookup: &[u16],
     skeleton: &Skeleton,
     skinning_weights: &SkinningWeights,
+    influences: &SkinInfluences,
 ) -> Mesh {
     // Allocate for all base mesh vertices the weights file references
     let max_weight_vertex = skinning_weights.max_vertex_index();
@@ -58,9 +63,14 @@ pub fn apply_skinning_weights_to_proxy(
             }
         }
 
-        // Convert to sorted vec and take top 4
-        let sorted: Vec<_> = bone_weights.into_iter().collect();
-        apply_top4_weights(&sorted, &mut indices[mesh_idx], &mut weights[mesh_idx]);
+        let bone_weights: Vec<_> = bone_weights.into_iter().collect();
+        limit_weights(
+            &bone_weights,
+            influences,
+            &skeleton.hierarchy,
+            &mut indices[mesh_idx],
+            &mut weights[mesh_idx],
+        );
     }
 
     mesh.insert_attribute(
@@ -294,22 +304,60 @@ pub fn get_vertex_uvs(mesh: &Mesh) -> Vec<[f32; 2]> {
         .unwrap_or_default()
 }
 
+/// Max distance for a mesh vertex to be considered the same as an obj vertex
+const VERTEX_MATCH_TOLERANCE: f32 = 0.0001;
+
+/// Maps obj vertex idx -> mesh vertex indices at the same position
+///
+/// Each mesh vertex matches the lowest obj index within tolerance. Obj vertices
+/// are bucketed into a grid with tolerance-sized cells, so a match can only be
+/// in the same or a neighboring cell.
 pub fn generate_vertex_map(
     obj_vertices: &[Vec3],
     mesh_vertices: &[Vec3],
 ) -> HashMap<u16, Vec<u16>> {
-    let mut vertex_map: HashMap<u16, Vec<u16>> = HashMap::default();
+    let cell = |v: Vec3| (v / VERTEX_MATCH_TOLERANCE).floor().as_ivec3();
+
+    // obj indices are pushed in order, so each bucket is sorted
+    let mut grid: HashMap<IVec3, Vec<u32>> = HashMap::with_capacity(obj_vertices.len());
+    for (obj_idx, obj_vert) in obj_vertices.iter().enumerate() {
+        grid.entry(cell(*obj_vert))
+            .or_default()
+            .push(obj_idx as u32);
+    }
 
+    let mut vertex_map: HashMap<u16, Vec<u16>> = HashMap::default();
     for (mesh_idx, mesh_vert) in mesh_vertices.iter().enumerate() {
-        for (obj_idx, obj_vert) in obj_vertices.iter().enumerate() {
-            if (mesh_vert - obj_vert).length() < 0.0001 {
-                vertex_map
-                    .entry(obj_idx as u16)
-                    .or_default()
-                    .push(mesh_idx as u16);
-                break;
+        let center = cell(*mesh_vert);
+        let mut best: Option<u32> = None;
+
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let Some(bucket) = grid.get(&(center + IVec3::new(dx, dy, dz))) else {
+                        continue;
+                    };
+                    for &obj_idx in bucket {
+                        if best.is_some_and(|b| b <= obj_idx) {
+                            break;
+                        }
+                        if (mesh_vert - obj_vertices[obj_idx as usize]).length()
+                            < VERTEX_MATCH_TOLERANCE
+                        {
+                            best = Some(obj_idx);
+                            break;
+                        }
+                    }
+                }
             }
         }
+
+        if let Some(obj_idx) = best {
+            vertex_map
+                .entry(obj_idx as u16)
+                .or_default()
+                .push(mesh_idx as u16);
+        }
     }
     vertex_map
 }
@@ -322,9 +370,16 @@ pub fn apply_skinning_weights_via_mhclo(
     mhid_lookup: &[u16],
     skeleton: &Skeleton,
     skinning_weights: &SkinningWeights,
+    own_weights: Option<&SkinningWeights>,
+    influences: &SkinInfluences,
 ) -> Mesh {
     let mesh_vert_count = get_vertex_positions(&mesh).len();
 
+    // The asset's own weights, per obj vertex
+    let own_vertex_weights = own_weights
+        .map(|w| own_vertex_weights(w, &skeleton.bone_indices))
+        .unwrap_or_default();
+
     // Convert sparse weights to per-vertex format for helpers
     // Prefer bindings (complete coverage) over vertex_mapping (may be partial)
     let max_helper_idx = if !mhclo.bindings.is_empty() {
@@ -360,6 +415,17 @@ pub fn apply_skinning_weights_via_mhclo(
             }
             let binding = &mhclo.bindings[obj_idx as usize];
 
+            if let Some(Some(own)) = own_vertex_weights.get(obj_idx as usize) {
+                limit_weights(
+                    own,
+                    influences,
+                    &skeleton.hierarchy,
+                    &mut indices[mesh_idx],
+                    &mut weights[mesh_idx],
+                );
+                continue;
+            }
+
             // Blend bone weights from 3 helper vertices using barycentric weights
             let mut bone_weights: HashMap<usize, f32> = HashMap::default();
 
@@ -376,9 +442,14 @@ pub fn apply_skinning_weights_via_mhclo(
                 }
             }
 
-            // Convert to sorted vec and take top 4
-            let sorted: Vec<_> = bone_weights.into_iter().collect();
-            apply_top4_weights(&sorted, &mut indices[mesh_idx], &mut weights[mesh_idx]);
+            let bone_weights: Vec<_> = bone_weights.into_iter().collect();
+            limit_weights(
+                &bone_weights,
+                influences,
+                &skeleton.hierarchy,
+                &mut indices[mesh_idx],
+                &mut weights[mesh_idx],
+            );
         }
     }
 
@@ -402,6 +473,7 @@ pub fn apply_morphed_base_mesh(
     morphed_vertices: &[Vec3],
     skeleton: &Skeleton,
     skinning_weights: &SkinningWeights,
+    influences: &SkinInfluences,
 ) -> Mesh {
     let mesh_verts = get_vertex_positions(base_mesh);
     let mesh_vert_count = mesh_verts.len();
@@ -429,9 +501,10 @@ pub fn apply_morphed_base_mesh(
     for (mesh_idx, &obj_idx) in mhid_lookup.iter().enumerate() {
         let base_idx = obj_idx as usize;
         if base_idx < base_vertex_weights.len() {
-            let bone_weights: Vec<_> = base_vertex_weights[base_idx].clone();
-            apply_top4_weights(
-                &bone_weights,
+            limit_weights(
+                &base_vertex_weights[base_idx],
+                influences,
+                &skeleton.hierarchy,
                 &mut indices[mesh_idx],
                 &mut weights[mesh_idx],
             );
@@ -454,15 +527,72 @@ pub fn apply_morphed_base_mesh(
     mesh
 }
 
-/// Helper to apply top 4 bone weights to mesh vertex
-fn apply_top4_weights(
+/// Own weights of an asset per obj vertex, remapped to skeleton bone indices
+///
+/// Weights on bones the skeleton lacks are dropped and the rest renormalized,
+/// None for vertices left without weights, those fall back to body weights.
+/// Weights made for another rig need [`SkinningWeights::remap_bones`] first
+fn own_vertex_weights(
+    weights: &SkinningWeights,
+    bone_indices: &HashMap<String, usize>,
+) -> Vec<Option<Vec<(usize, f32)>>> {
+    let mut vertex_weights: Vec<Vec<(usize, f32)>> =
+        vec![Vec::new(); weights.max_vertex_index() + 1];
+    for (bone_name, bone_weights) in &weights.weights {
+        let Some(&bone_idx) = bone_indices.get(bone_name) else {
+            continue;
+        };
+        for &(vert_idx, weight) in bone_weights {
+            vertex_weights[vert_idx].push((bone_idx, weight));
+        }
+    }
+    vertex_weights
+        .into_iter()
+        .map(|mut w| {
+            let sum: f32 = w.iter().map(|(_, w)| w).sum();
+            if sum <= 1e-6 {
+                return None;
+            }
+            w.iter_mut().for_each(|(_, w)| *w /= sum);
+            Some(w)
+        })
+        .collect()
+}
+
+/// Cut a vertex's joint weights down to [`SkinInfluences`] and write them as Bevy joint attributes
+fn limit_weights(
     bone_weights: &[(usize, f32)],
+    influences: &SkinInfluences,
+    hierarchy: &[Option<usize>],
     indices: &mut [u16; 4],
     weights: &mut [f32; 4],
 ) {
     let mut sorted = bone_weights.to_vec();
-    sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-    sorted.truncate(4);
+    sorted.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    // keep the heaviest even if it's under min_weight
+    let above_min = sorted
+        .iter()
+        .skip(1)
+        .take_while(|(_, w)| *w >= influences.min_weight)
+        .count()
+        + 1;
+    sorted.truncate(above_min);
+
+    let max = influences
+        .max_influences
+        .clamp(1, SkinInfluences::MAX_JOINTS);
+    let pruned = sorted.split_off(max.min(sorted.len()));
+    if influences.pruning == WeightPruning::Redistribute {
+        for (bone, weight) in pruned {
+            if let Some(nearest) = sorted
+                .iter_mut()
+                .min_by_key(|(kept, _)| bone_distance(hierarchy, bone, *kept))
+            {
+                nearest.1 += weight;
+            }
+        }
+    }
 
     let sum: f32 = sorted.iter().map(|(_, w)| w).sum();
 
@@ -483,29 +613,50 @@ fn apply_top4_weights(
     }
 }
 
+/// Bones between `a` and `b` through their common ancestor, usize::MAX if unconnected
+fn bone_distance(hierarchy: &[Option<usize>], a: usize, b: usize) -> usize {
+    let mut ancestors = vec![a];
+    while let Some(&Some(parent)) = hierarchy.get(*ancestors.last().unwrap()) {
+        ancestors.push(parent);
+    }
+
+    let mut bone = b;
+    for depth in 0.. {
+        if let Some(i) = ancestors.iter().position(|&x| x == bone) {
+            return i + depth;
+        }
+        match hierarchy.get(bone) {
+            Some(&Some(parent)) => bone = parent,
+            _ => break,
+        }
+    }
+    usize::MAX
+}
+
 /// Transfer weights from helpers to proxy/accessory mesh vertices
 /// Uses proximity-based weight blending from nearby helper vertices
 pub fn transfer_weights_from_helpers(
     asset_vertices: &[Vec3],
     morphed_helpers: &[Vec3],
     helper_weights: &SkinningWeights,
-    bone_indices: &HashMap<String, usize>,
+    skeleton: &Skeleton,
+    influences: &SkinInfluences,
 ) -> (Vec<[u16; 4]>, Vec<[f32; 4]>) {
     let mut vertex_indices = Vec::with_capacity(asset_vertices.len());
     let mut vertex_weights = Vec::with_capacity(asset_vertices.len());
 
     // Convert helper weights to per-vertex format
     let helper_vertex_weights =
-        helper_weights.to_vertex_weights(bone_indices, morphed_helpers.len());
+        helper_weights.to_vertex_weights(&skeleton.bone_indices, morphed_helpers.len());
 
     for asset_vert in asset_vertices {
         // Find 3 closest helpers for interpolation
-        let influences = find_closest_helpers(asset_vert, morphed_helpers, 3);
+        let nearest = find_closest_helpers(asset_vert, morphed_helpers, 3);
 
         // Blend bone weights from nearby helpers
         let mut bone_weights: HashMap<usize, f32> = HashMap::new();
 
-        for (helper_id, influence_weight) in influences {
+        for (helper_id, influence_weight) in nearest {
             if helper_id < helper_vertex_weights.len() {
                 for &(bone_idx, bone_weight) in &helper_vertex_weights[helper_id] {
                     *bone_weights.entry(bone_idx).or_insert(0.0) += bone_weight * influence_weight;
@@ -513,33 +664,16 @@ pub fn transfer_weights_from_helpers(
             }
         }
 
-        // Get top 4 bones (Bevy limit)
-        let mut sorted: Vec<_> = bone_weights.into_iter().collect();
-        sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-        sorted.truncate(4);
-
-        // Normalize weights
-        let sum: f32 = sorted.iter().map(|(_, w)| w).sum();
-
+        let bone_weights: Vec<_> = bone_weights.into_iter().collect();
         let mut indices = [0u16; 4];
         let mut weights = [0f32; 4];
-
-        if sum > 1e-6 {
-            for (i, (bone_idx, weight)) in sorted.iter().enumerate() {
-                indices[i] = *bone_idx as u16;
-                weights[i] = weight / sum;
-            }
-
-            // Fill remaining with first bone (zero weight)
-            for i in sorted.len()..4 {
-                indices[i] = indices[0];
-                weights[i] = 0.0;
-            }
-        } else {
-            // No weights found - use default (bone 0)
-            indices[0] = 0;
-            weights[0] = 1.0;
-        }
+        limit_weights(
+            &bone_weights,
+            influences,
+            &skeleton.hierarchy,
+            &mut indices,
+            &mut weights,
+        );
 
         vertex_indices.push(indices);
         vertex_weights.push(weights);
@@ -585,6 +719,58 @@ fn find_closest_helpers(point: &Vec3, helpers: &[Vec3], count: usize) -> Vec<(us
         .collect()
 }
 
+/// Torso height band, as fraction of body height, measured for [`torso_radius`]
+const TORSO_BAND: (f32, f32) = (0.45, 0.65);
+
+/// Horizontal spacing that separates the torso from the arms
+const TORSO_ARM_GAP: f32 = 0.04;
+
+/// Horizontal radius of the torso, the larger of half width and half depth
+pub fn torso_radius(positions: &[Vec3], min_y: f32, height: f32) -> Option<f32> {
+    let (lo, hi) = (min_y + height * TORSO_BAND.0, min_y + height * TORSO_BAND.1);
+    let (center, torso) = torso_slice(positions, lo, hi)?;
+    let half_width = torso
+        .iter()
+        .map(|p| (p.x - center.x).abs())
+        .fold(0.0, f32::max);
+    let half_depth = torso
+        .iter()
+        .map(|p| (p.y - center.y).abs())
+        .fold(0.0, f32::max);
+    Some(half_width.max(half_depth))
+}
+
+/// Horizontal (xz) center and points of the torso between heights `lo` and `hi`
+///
+/// Arms hang next to the torso in the rest pose, width stops at the first gap
+/// between torso and arm vertices
+pub fn torso_slice(positions: &[Vec3], lo: f32, hi: f32) -> Option<(Vec2, Vec<Vec2>)> {
+    let band: Vec<Vec2> = positions
+        .iter()
+        .filter(|p| (lo..=hi).contains(&p.y))
+        .map(|p| p.xz())
+        .collect();
+    if band.is_empty() {
+        return None;
+    }
+
+    let center = band.iter().sum::<Vec2>() / band.len() as f32;
+
+    let mut dists: Vec<f32> = band.iter().map(|p| (p.x - center.x).abs()).collect();
+    dists.sort_by(f32::total_cmp);
+    let half_width = dists
+        .windows(2)
+        .find(|w| w[1] - w[0] > TORSO_ARM_GAP)
+        .map(|w| w[0])
+        .unwrap_or(*dists.last()?);
+
+    let torso = band
+        .into_iter()
+        .filter(|p| (p.x - center.x).abs() <= half_width)
+        .collect();
+    Some((center, torso))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -608,4 +794,172 @@ mod tests {
         let sum: f32 = vertex_weights[0].iter().map(|(_, w)| w).sum();
         assert!((sum - 1.0).abs() < 1e-6, "Weights should sum to 1.0");
     }
+
+    #[test]
+    fn test_vertex_map_matches_lowest_obj_index() {
+        // obj 0 and 2 are duplicates, obj 1 sits right on a cell boundary
+        let obj = vec![
+            Vec3::new(0.1, 0.2, 0.3),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.2, 0.3),
+            Vec3::new(1.0, 1.0, 1.0),
+        ];
+        let mesh = vec![
+            Vec3::new(0.1, 0.2, 0.3),
+            Vec3::new(0.00005, -0.00005, 0.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(0.1, 0.2, 0.3),
+            Vec3::new(0.5, 0.5, 0.5), // no match
+        ];
+
+        let map = generate_vertex_map(&obj, &mesh);
+
+        assert_eq!(map.get(&0), Some(&vec![0, 3]));
+        assert_eq!(map.get(&1), Some(&vec![1]));
+        assert_eq!(map.get(&2), None);
+        assert_eq!(map.get(&3), Some(&vec![2]));
+        assert_eq!(map.values().map(Vec::len).sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn test_torso_radius_ignores_arms() {
+        let mut positions = vec![Vec3::ZERO, Vec3::Y * 2.0];
+        // torso ellipse 0.15 wide, 0.1 deep
+        for i in 0..64 {
+            let a = i as f32 / 64.0 * std::f32::consts::TAU;
+            for y in [0.9, 1.0, 1.1, 1.2] {
+                positions.push(Vec3::new(a.cos() * 0.15, y, a.sin() * 0.1));
+                // arms hanging 0.25 out
+                positions.push(Vec3::new(0.25 + a.cos() * 0.04, y, a.sin() * 0.04));
+                positions.push(Vec3::new(-0.25 + a.cos() * 0.04, y, a.sin() * 0.04));
+            }
+        }
+
+        let radius = torso_radius(&positions, 0.0, 2.0).unwrap();
+        assert!((radius - 0.15).abs() < 0.01, "radius {radius}");
+        assert_eq!(torso_radius(&[], 0.0, 2.0), None);
+    }
+
+    #[test]
+    fn test_redistribute_moves_pruned_weight_to_nearest_bone() {
+        // 0 root, 1 child of 0, 2 child of 1, 3 child of 0
+        let hierarchy = [None, Some(0), Some(1), Some(0)];
+        let bone_weights = [(2, 0.5), (1, 0.3), (3, 0.2)];
+        let mut influences = SkinInfluences {
+            max_influences: 2,
+            ..default()
+        };
+
+        let (mut indices, mut weights) = ([0u16; 4], [0f32; 4]);
+        limit_weights(
+            &bone_weights,
+            &influences,
+            &hierarchy,
+            &mut indices,
+            &mut weights,
+        );
+        assert_eq!(indices[..2], [2, 1]);
+        assert!((weights[0] - 0.625).abs() < 1e-6);
+
+        influences.pruning = WeightPruning::Redistribute;
+        limit_weights(
+            &bone_weights,
+            &influences,
+            &hierarchy,
+            &mut indices,
+            &mut weights,
+        );
+        assert_eq!(indices[..2], [2, 1]);
+        assert!((weights[0] - 0.5).abs() < 1e-6 && (weights[1] - 0.5).abs() < 1e-6);
+        assert_eq!(weights[2..], [0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_skin_influences_validated() {
+        let influences = |max_influences| SkinInfluences {
+            max_influences,
+            ..default()
+        };
+        assert_eq!(influences(8).validated().max_influences, 4);
+        assert_eq!(influences(0).validated().max_influences, 1);
+        assert_eq!(influences(3).validated(), influences(3));
+
+        // limit_weights never writes past the 4 joints either way
+        let bone_weights = [(0, 0.3), (1, 0.25), (2, 0.2), (3, 0.15), (4, 0.1)];
+        let (mut indices, mut weights) = ([0u16; 4], [0f32; 4]);
+        limit_weights(
+            &bone_weights,
+            &influences(8),
+            &[None; 5],
+            &mut indices,
+            &mut weights,
+        );
+        assert_eq!(indices, [0, 1, 2, 3]);
+        assert!((weights.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+    }
+
+    fn skinning(bones: &[(&str, &[(usize, f32)])]) -> SkinningWeights {
+        SkinningWeights {
+            weights: bones
+                .iter()
+                .map(|(bone, weights)| (bone.to_string(), weights.to_vec()))
+                .collect(),
+        }
+    }
+
+    fn sorted(weights: &Option<Vec<(usize, f32)>>) -> Option<Vec<(usize, f32)>> {
+        weights.clone().map(|mut w| {
+            w.sort_by_key(|(bone, _)| *bone);
+            w
+        })
+    }
+
+    #[test]
+    fn test_own_vertex_weights_matched_bones() {
+        let own = skinning(&[("bone_a", &[(0, 0.6)]), ("bone_b", &[(0, 0.4), (1, 1.0)])]);
+        let bone_indices =
+            HashMap::from_iter([("bone_a".to_string(), 0), ("bone_b".to_string(), 1)]);
+
+        let vertex_weights = own_vertex_weights(&own, &bone_indices);
+        assert_eq!(sorted(&vertex_weights[0]), Some(vec![(0, 0.6), (1, 0.4)]));
+        assert_eq!(vertex_weights[1], Some(vec![(1, 1.0)]));
+    }
+
+    #[test]
+    fn test_own_vertex_weights_missing_bones() {
+        // vertex 0 keeps bone_a renormalized, vertex 1 has only the missing bone and falls back
+        let own = skinning(&[("bone_a", &[(0, 0.5)]), ("missing", &[(0, 0.5), (1, 1.0)])]);
+        let bone_indices = HashMap::from_iter([("bone_a".to_string(), 0)]);
+
+        let vertex_weights = own_vertex_weights(&own, &bone_indices);
+        assert_eq!(vertex_weights, vec![Some(vec![(0, 1.0)]), None]);
+    }
+
+    #[test]
+    fn test_own_vertex_weights_other_rig() {
+        // reference rig arm covers vertices 0-1, the target rig splits them between two bones
+        let reference = skinning(&[
+            ("upperarm01.L", &[(0, 1.0), (1, 1.0)]),
+            ("spine01", &[(2, 1.0)]),
+        ]);
+        let rig = skinning(&[
+            ("LeftArm", &[(0, 1.0), (1, 0.5)]),
+            ("LeftForeArm", &[(1, 0.5)]),
+            ("Spine", &[(2, 1.0)]),
+        ]);
+        let bone_indices = HashMap::from_iter([
+            ("Spine".to_string(), 0),
+            ("LeftArm".to_string(), 1),
+            ("LeftForeArm".to_string(), 2),
+        ]);
+
+        let bone_map = reference.bone_map(&rig);
+        let own = skinning(&[("upperarm01.L", &[(0, 1.0)]), ("spine01", &[(1, 1.0)])]);
+        let vertex_weights = own_vertex_weights(&own.remap_bones(&bone_map), &bone_indices);
+
+        let arm = sorted(&vertex_weights[0]).unwrap();
+        assert_eq!(arm.iter().map(|(b, _)| *b).collect::<Vec<_>>(), [1, 2]);
+        assert!((arm[0].1 - 0.75).abs() < 1e-6 && (arm[1].1 - 0.25).abs() < 1e-6);
+        assert_eq!(vertex_weights[1], Some(vec![(0, 1.0)]));
+    }
 }